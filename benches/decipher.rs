@@ -0,0 +1,180 @@
+//! Benchmarks deciphering all formats of the fixture video.
+//!
+//! `decipher_format` goes through [`Innertube`] with a transport serving the fixtures, the
+//! `context` group compares creating a js context for every format with reusing one, and the
+//! `n_cache` group compares a cipher without cached ncode results with one that has them.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use http::HeaderMap;
+use rquickjs::{async_with, AsyncContext, AsyncRuntime};
+use tokio::runtime::Runtime;
+use yinfo::{
+    cipher::Cipher,
+    transport::{BoxFuture, HttpResponse},
+    ClientConfig, ClientType, Config, Error, HttpTransport, Innertube, VideoFormat,
+};
+
+const PLAYER_JSON: &str = include_str!("../tests/fixtures/player.json");
+const PLAYER_JS: &str = include_str!("../tests/fixtures/player.js");
+
+/// Serves the player response and player js from the fixtures.
+#[derive(Debug)]
+struct FixtureTransport;
+
+impl FixtureTransport {
+    fn respond(url: &str) -> Result<HttpResponse, Error> {
+        let body = if url.contains("/youtubei/v1/player") {
+            PLAYER_JSON
+        } else if url.ends_with("/embed/") {
+            r#"<script>ytcfg.set({"jsUrl":"/s/player/6e1dd460/player_ias.vflset/en_US/base.js"});</script>"#
+        } else if url.ends_with("/base.js") {
+            PLAYER_JS
+        } else {
+            return Ok(HttpResponse {
+                status: 404,
+                body: String::new(),
+            });
+        };
+        Ok(HttpResponse {
+            status: 200,
+            body: body.to_owned(),
+        })
+    }
+}
+
+impl HttpTransport for FixtureTransport {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a str,
+        _headers: HeaderMap,
+        _body: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move { Self::respond(url) })
+    }
+
+    fn get_text<'a>(
+        &'a self,
+        url: &'a str,
+        _headers: HeaderMap,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move { Self::respond(url) })
+    }
+}
+
+fn innertube() -> Innertube {
+    Innertube::new(Config {
+        configs: vec![ClientConfig::new(ClientType::Ios)],
+        retry_limit: 0,
+        ..Config::with_client(FixtureTransport)
+    })
+    .unwrap()
+}
+
+/// Returns the formats of the fixture video which can be deciphered.
+async fn formats(innertube: &Innertube) -> Vec<VideoFormat> {
+    let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+    let mut formats = Vec::new();
+    for format in video.all_formats() {
+        if innertube.decipher_format(format).await.is_ok() {
+            formats.push(format.clone());
+        }
+    }
+    assert!(!formats.is_empty());
+    formats
+}
+
+fn bench_decipher_format(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let innertube = innertube();
+    let formats = rt.block_on(formats(&innertube));
+
+    c.bench_function("decipher_format", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for format in &formats {
+                    innertube.decipher_format(format).await.unwrap();
+                }
+            });
+        });
+    });
+}
+
+fn bench_context(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let formats = rt.block_on(formats(&innertube()));
+    let runtime = AsyncRuntime::new().unwrap();
+    let cipher = &Cipher::new(PLAYER_JS);
+
+    let mut group = c.benchmark_group("context");
+    group.bench_function("per_format", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for format in &formats {
+                    let context = AsyncContext::full(&runtime).await.unwrap();
+                    async_with!(context => |ctx| { cipher.apply(&ctx, format) })
+                        .await
+                        .unwrap();
+                }
+            });
+        });
+    });
+    group.bench_function("reused", |b| {
+        let context = rt.block_on(AsyncContext::full(&runtime)).unwrap();
+        b.iter(|| {
+            rt.block_on(async {
+                for format in &formats {
+                    async_with!(context => |ctx| { cipher.apply(&ctx, format) })
+                        .await
+                        .unwrap();
+                }
+            });
+        });
+    });
+    group.finish();
+}
+
+fn bench_n_cache(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let formats = rt.block_on(formats(&innertube()));
+    let runtime = AsyncRuntime::new().unwrap();
+    let context = rt.block_on(AsyncContext::full(&runtime)).unwrap();
+
+    let mut group = c.benchmark_group("n_cache");
+    group.bench_function("cold", |b| {
+        // A new cipher for every format, so each ncode is evaluated
+        b.iter_batched(
+            || {
+                formats
+                    .iter()
+                    .map(|_| Cipher::new(PLAYER_JS))
+                    .collect::<Vec<_>>()
+            },
+            |ciphers| {
+                rt.block_on(async {
+                    for (cipher, format) in ciphers.iter().zip(&formats) {
+                        async_with!(context => |ctx| { cipher.apply(&ctx, format) })
+                            .await
+                            .unwrap();
+                    }
+                });
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    group.bench_function("warm", |b| {
+        let cipher = &Cipher::new(PLAYER_JS);
+        b.iter(|| {
+            rt.block_on(async {
+                for format in &formats {
+                    async_with!(context => |ctx| { cipher.apply(&ctx, format) })
+                        .await
+                        .unwrap();
+                }
+            });
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_decipher_format, bench_context, bench_n_cache);
+criterion_main!(benches);
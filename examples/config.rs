@@ -5,7 +5,7 @@ async fn main() {
     let config = Config {
         configs: vec![ClientConfig::new(ClientType::Web)],
         retry_limit: 2,
-        ..Default::default()
+        ..Config::with_client(reqwest::Client::new())
     };
     let innertube = Innertube::new(config).unwrap();
     let video = innertube.info("RhmHSAClG1c").await.unwrap();
@@ -0,0 +1,16 @@
+use yinfo::{Config, Innertube};
+
+#[tokio::main]
+async fn main() {
+    let innertube = Innertube::new(Config::default()).unwrap();
+    let video = innertube.info("RhmHSAClG1c").await.unwrap();
+
+    if let Some(url) = &video.streaming_data.dash_manifest_url {
+        match innertube.fetch_dash_manifest(url).await {
+            Ok(manifest) => println!("{}", manifest),
+            Err(why) => println!("{}", why),
+        }
+    } else {
+        println!("No DASH manifest found");
+    }
+}
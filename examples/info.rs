@@ -6,6 +6,13 @@ async fn main() {
     let video = innertube.info("RhmHSAClG1c").await.unwrap();
     println!("{:#?}", video);
 
+    println!(
+        "{} ({:?}, {} views)",
+        video.title(),
+        video.duration().unwrap_or_default(),
+        video.video_details.view_count
+    );
+
     let format = video.best_audio();
     if let Some(f) = format {
         let url = innertube.decipher_format(f).await;
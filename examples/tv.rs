@@ -0,0 +1,27 @@
+use yinfo::{clients::ClientType, Innertube};
+
+#[tokio::main]
+async fn main() {
+    let innertube = Innertube::builder()
+        .client(ClientType::Tv)
+        .client(ClientType::TvEmbedded)
+        .build()
+        .unwrap();
+    let video = innertube.info("RhmHSAClG1c").await.unwrap();
+    println!(
+        "{} ({} formats)",
+        video.title(),
+        video.all_formats().count()
+    );
+
+    let format = video.best_audio();
+    if let Some(f) = format {
+        let url = innertube.decipher_format(f).await;
+        match url {
+            Ok(x) => println!("{}", x),
+            Err(why) => println!("{}", why),
+        }
+    } else {
+        println!("No formats found");
+    }
+}
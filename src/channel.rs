@@ -0,0 +1,255 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{query::Text, structs::Thumbnail};
+
+/// Metadata of a channel, returned by [`crate::innertube::Innertube::channel_info()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChannelInfo {
+    pub channel_id: String,
+    pub title: String,
+    pub description: String,
+    /// Subscriber count as displayed by YouTube, such as `"1.2M subscribers"`.
+    pub subscriber_count_text: Option<String>,
+    pub avatar_thumbnails: Vec<Thumbnail>,
+    pub banner_thumbnails: Vec<Thumbnail>,
+}
+
+/// Attempt to get the channel id from a YouTube channel url, or the id itself.
+pub(crate) fn get_channel_id(url: &str) -> Option<&str> {
+    const CHANNEL_URL: &str = r"(?x)
+        ^(?:https?://)?                     # protocol
+        (?:(?:www|m)\.)?youtube\.com        # hostname
+        /channel/
+        (UC[[:word:]-]{22})                 # id
+        (?:[/?\#].*)?$
+    ";
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(CHANNEL_URL).unwrap());
+
+    if let Some(cap) = PATTERN.captures(url) {
+        return Some(cap.get(1).unwrap().as_str());
+    } else if url.len() == 24
+        && url.starts_with("UC")
+        && url
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Some(url);
+    }
+    None
+}
+
+/// Attempt to get the url of a channel given by its handle, custom url or legacy username, such
+/// as `https://www.youtube.com/@handle` or `@handle`. These have to be resolved to a channel id
+/// with an additional request.
+pub(crate) fn get_channel_url(url: &str) -> Option<String> {
+    const VANITY_URL: &str = r"(?x)
+        ^(?:(?:https?://)?                  # protocol
+        (?:(?:www|m)\.)?youtube\.com/)?     # hostname, may be omitted for handles
+        (
+         @[[:word:].-]+                     # handle
+         | (?:c|user)/[[:word:].-]+         # custom url or username
+        )
+        (?:[/?\#].*)?$
+    ";
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(VANITY_URL).unwrap());
+
+    let path = PATTERN.captures(url)?.get(1)?.as_str();
+    // Only handles can be given without the hostname
+    if !path.starts_with('@') && !url.contains("youtube.com/") {
+        return None;
+    }
+    Some(format!("https://www.youtube.com/{path}"))
+}
+
+/// Response of the resolve url endpoint, used to get the channel id of a channel url.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResolveUrl {
+    endpoint: ResolveEndpoint,
+}
+
+impl ResolveUrl {
+    /// Returns the channel id the url resolved to, if it resolved to a channel at all.
+    pub(crate) fn channel_id(self) -> Option<String> {
+        self.endpoint
+            .browse_endpoint
+            .map(|x| x.browse_id)
+            .filter(|x| x.starts_with("UC"))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveEndpoint {
+    browse_endpoint: Option<BrowseEndpoint>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseEndpoint {
+    browse_id: String,
+}
+
+// Response structures of the browse endpoint for channels.
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChannelBrowse {
+    header: Option<ChannelHeader>,
+    metadata: Option<ChannelMetadata>,
+}
+
+impl ChannelBrowse {
+    pub(crate) fn info(self, channel_id: &str) -> ChannelInfo {
+        let header = self.header.and_then(|x| x.c4_tabbed_header_renderer);
+        let metadata = self.metadata.map(|x| x.channel_metadata_renderer);
+
+        let (subscriber_count_text, banner_thumbnails, header_avatar) = match header {
+            Some(header) => (
+                header.subscriber_count_text.as_ref().map(Text::text),
+                header.banner.map(|x| x.thumbnails).unwrap_or_default(),
+                header.avatar.map(|x| x.thumbnails),
+            ),
+            None => (None, Vec::new(), None),
+        };
+
+        match metadata {
+            Some(metadata) => ChannelInfo {
+                channel_id: metadata
+                    .external_id
+                    .unwrap_or_else(|| channel_id.to_owned()),
+                title: metadata.title,
+                description: metadata.description,
+                subscriber_count_text,
+                avatar_thumbnails: metadata
+                    .avatar
+                    .map(|x| x.thumbnails)
+                    .or(header_avatar)
+                    .unwrap_or_default(),
+                banner_thumbnails,
+            },
+            None => ChannelInfo {
+                channel_id: channel_id.to_owned(),
+                title: String::new(),
+                description: String::new(),
+                subscriber_count_text,
+                avatar_thumbnails: header_avatar.unwrap_or_default(),
+                banner_thumbnails,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelHeader {
+    c4_tabbed_header_renderer: Option<C4TabbedHeaderRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct C4TabbedHeaderRenderer {
+    subscriber_count_text: Option<Text>,
+    avatar: Option<Thumbnails>,
+    banner: Option<Thumbnails>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelMetadata {
+    channel_metadata_renderer: ChannelMetadataRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelMetadataRenderer {
+    title: String,
+    #[serde(default)]
+    description: String,
+    external_id: Option<String>,
+    avatar: Option<Thumbnails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Thumbnails {
+    thumbnails: Vec<Thumbnail>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_channel_id() {
+        let strings = [
+            "UCuAXFkgsw1L7xaCfnd5JJOw",
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "http://youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw/videos",
+            "https://m.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw?view=0",
+        ];
+        for str in strings {
+            assert_eq!(get_channel_id(str), Some("UCuAXFkgsw1L7xaCfnd5JJOw"));
+        }
+    }
+
+    #[test]
+    fn test_get_channel_id_bad() {
+        let strings = [
+            "UCuAXFkgsw1L7xaCfnd5JJ",
+            "PLuAXFkgsw1L7xaCfnd5JJOw",
+            "https://www.youtube.com/@RickAstleyYT",
+            "https://example.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+        ];
+        for str in strings {
+            assert_eq!(get_channel_id(str), None);
+        }
+    }
+
+    #[test]
+    fn test_get_channel_url() {
+        let strings = [
+            ("@RickAstleyYT", "https://www.youtube.com/@RickAstleyYT"),
+            (
+                "https://www.youtube.com/@RickAstleyYT/videos",
+                "https://www.youtube.com/@RickAstleyYT",
+            ),
+            (
+                "youtube.com/c/RickAstleyVEVO",
+                "https://www.youtube.com/c/RickAstleyVEVO",
+            ),
+            (
+                "https://m.youtube.com/user/RickAstleyVEVO?app=desktop",
+                "https://www.youtube.com/user/RickAstleyVEVO",
+            ),
+        ];
+        for (str, url) in strings {
+            assert_eq!(get_channel_url(str).as_deref(), Some(url));
+        }
+
+        let strings = [
+            "c/RickAstleyVEVO",
+            "RickAstleyYT",
+            "https://example.com/@RickAstleyYT",
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+        ];
+        for str in strings {
+            assert_eq!(get_channel_url(str), None);
+        }
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let json = r#"{"endpoint": {"browseEndpoint": {"browseId": "UCuAXFkgsw1L7xaCfnd5JJOw", "canonicalBaseUrl": "/@RickAstleyYT"}}}"#;
+        let res: ResolveUrl = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            res.channel_id().as_deref(),
+            Some("UCuAXFkgsw1L7xaCfnd5JJOw")
+        );
+
+        let json = r#"{"endpoint": {"urlEndpoint": {"url": "https://www.youtube.com/"}}}"#;
+        let res: ResolveUrl = serde_json::from_str(json).unwrap();
+        assert_eq!(res.channel_id(), None);
+    }
+}
@@ -1,57 +1,160 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    num::NonZeroUsize,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use url::{
     form_urlencoded::{parse, Serializer},
     Url,
 };
 
+use lru::LruCache;
 use once_cell::sync::Lazy;
-use regex::{escape, Regex};
+use regex::Regex;
 
 use rquickjs::Ctx;
 
-use crate::{errors::Error, structs::VideoFormat, utils::between};
+use crate::{errors::Error, mime::Mime, structs::VideoFormat, utils::between};
 
 /// Operations used inside the player js code to decipher the stream url. The operations
 /// are javascript code all doing a specific function, such as swapping or reversing.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum Operation {
-    Swap(usize),
+    /// Swaps the first element with the one at the index.
+    Swap(Arg),
     Reverse(),
-    Slice(usize),
-    Splice(usize),
+    /// Keeps the elements from the start up to the optional end, `a.slice(b,c)`.
+    Slice(Arg, Option<Arg>),
+    /// Removes the given number of elements starting at the index, or all elements from the
+    /// index on if no number is given, like `a.splice(b,c)`.
+    Splice(Arg, Option<Arg>),
 }
 
 impl Operation {
     /// Creates a new operation with the given definition and parameter. The definition is a slice
-    /// of the javascript code and the parameter is usually an integer.
+    /// of the javascript code and the parameter is usually an integer, which may be missing for
+    /// operations not using it.
     ///
     /// # Errors
     ///
-    /// An error is returned if no operations can be found.
-    pub fn new(def: &str, param: &str) -> Result<Self, Error> {
+    /// An error is returned if no operations can be found, or the operation uses a parameter
+    /// which is missing or not an integer.
+    pub fn new(def: &str, param: Option<&str>) -> Result<Self, Error> {
         // TODO: might be possible to use non-regex method and use string patterns instead
         static REVERSE: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"(?:return )?a\.reverse\(\)").unwrap());
-        static SLICE: Lazy<Regex> = Lazy::new(|| Regex::new(r"return a\.slice\(b\)").unwrap());
-        static SPLICE: Lazy<Regex> = Lazy::new(|| Regex::new(r"a\.splice\(0,b\)").unwrap());
+        static SLICE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"a\.slice\(([^,)]+)(?:,([^)]+))?\)").unwrap());
+        static SPLICE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"a\.splice\(([^,)]+)(?:,([^)]+))?\)").unwrap());
         static SWAP: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"var c=a\[0\];a\[0\]=a\[b%a\.length\];a\[b(?:%a.length|)\]=c(?:;return a)?")
-                .unwrap()
+            Regex::new(r"var c=a\[0\];a\[0\]=a\[([^\]]+)\];a\[[^\]]+\]=c(?:;return a)?").unwrap()
         });
 
-        // TODO: probably not a good idea to unconditionally unwrap to 0
-        let param = param.parse::<usize>().unwrap_or(0);
+        let param = param.and_then(|x| x.parse::<isize>().ok());
+        let invalid = || Error::Cipher(format!("invalid operation '{def}'"));
+        let args = |captures: regex::Captures| {
+            let start = Arg::parse(&captures[1], param).ok_or_else(invalid)?;
+            let end = captures
+                .get(2)
+                .map(|x| Arg::parse(x.as_str(), param).ok_or_else(invalid))
+                .transpose()?;
+            Ok::<_, Error>((start, end))
+        };
+
         if REVERSE.is_match(def) {
             Ok(Operation::Reverse())
-        } else if SLICE.is_match(def) {
-            Ok(Operation::Slice(param))
-        } else if SPLICE.is_match(def) {
-            Ok(Operation::Splice(param))
-        } else if SWAP.is_match(def) {
-            Ok(Operation::Swap(param))
+        } else if let Some(captures) = SLICE.captures(def) {
+            let (start, end) = args(captures)?;
+            Ok(Operation::Slice(start, end))
+        } else if let Some(captures) = SPLICE.captures(def) {
+            let (start, count) = args(captures)?;
+            Ok(Operation::Splice(start, count))
+        } else if let Some(captures) = SWAP.captures(def) {
+            Ok(Operation::Swap(
+                Arg::parse(&captures[1], param).ok_or_else(invalid)?,
+            ))
+        } else {
+            Err(invalid())
+        }
+    }
+
+    /// Applies the operation with the same semantics as javascript, empty arrays are left as is.
+    fn apply(&self, chars: &mut Vec<char>) {
+        let len = chars.len();
+        match self {
+            Operation::Swap(index) => {
+                let index = index.index(len);
+                if index < len {
+                    chars.swap(0, index);
+                }
+            }
+            Operation::Reverse() => chars.reverse(),
+            Operation::Slice(start, end) => {
+                let start = start.index(len);
+                let end = end.map_or(len, |x| x.index(len));
+                chars.truncate(end.max(start));
+                chars.drain(..start);
+            }
+            Operation::Splice(start, count) => {
+                let start = start.index(len);
+                let count = count.map_or(len, |x| x.count(len)).min(len - start);
+                chars.drain(start..start + count);
+            }
+        }
+    }
+}
+
+/// Argument of an [`Operation`], resolved from the parameter passed to the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arg {
+    Value(isize),
+    /// The value modulo the length of the array, `b%a.length`.
+    ModLength(isize),
+}
+
+impl Arg {
+    /// Parses an argument such as `b`, `-b`, `b%a.length` or a number, where `b` is the
+    /// parameter.
+    fn parse(arg: &str, param: Option<isize>) -> Option<Self> {
+        let (arg, modulo) = match arg.trim().strip_suffix("%a.length") {
+            Some(arg) => (arg, true),
+            None => (arg.trim(), false),
+        };
+        let value = match arg {
+            "b" => param?,
+            "-b" => -param?,
+            _ => arg.parse().ok()?,
+        };
+        Some(if modulo {
+            Arg::ModLength(value)
         } else {
-            Err(Error::Cipher(format!("invalid operation '{def}'")))
+            Arg::Value(value)
+        })
+    }
+
+    /// Resolves the argument as an index into an array of the given length, negative values count
+    /// from the end. The index is at most the length.
+    fn index(self, len: usize) -> usize {
+        let signed = isize::try_from(len).unwrap_or(isize::MAX);
+        let index = match self {
+            Arg::ModLength(_) if len == 0 => 0,
+            Arg::ModLength(x) => x.rem_euclid(signed),
+            Arg::Value(x) if x < 0 => (signed + x).max(0),
+            Arg::Value(x) => x.min(signed),
+        };
+        usize::try_from(index).unwrap_or(0)
+    }
+
+    /// Resolves the argument as a number of elements, negative values count as none.
+    fn count(self, len: usize) -> usize {
+        match self {
+            Arg::Value(x) => usize::try_from(x).unwrap_or(0),
+            Arg::ModLength(_) => self.index(len),
         }
     }
 }
@@ -71,13 +174,18 @@ impl Operation {
 /// The operations for the signature are mandatory for deciphering the stream but the ncode is
 /// not required but results in the download being throttled. The signature operations are relatively
 /// short and can be translated natively, but the ncode operations are quite long which is why
-/// quickjs is used to execute it.
+/// quickjs is used to execute it. Results of the ncode are cached, as the same ncode is shared by
+/// all formats of a video.
 pub struct Cipher {
     operations: Option<Vec<Operation>>,
     nfunc: Option<String>,
     timestamp: Option<String>,
+    n_cache: Mutex<LruCache<String, String>>,
 }
 
+/// Number of ncode results kept per player js.
+const N_CACHE_SIZE: usize = 256;
+
 impl Cipher {
     /// Creates a cipher solution for the given url after parsing the code.
     #[must_use]
@@ -86,6 +194,9 @@ impl Cipher {
             operations: extract_operations(player_js),
             nfunc: extract_nfunc(player_js),
             timestamp: extract_timestamp(player_js),
+            n_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(N_CACHE_SIZE).expect("cache size is not zero"),
+            )),
         }
     }
 
@@ -101,7 +212,9 @@ impl Cipher {
     ///
     /// An error is returned if any data is missing in the video format, such as the signature or
     /// the url. Other errors include failing to decipher the signature or failing to execute js.
-    pub fn apply(&self, context: &Ctx, format: &VideoFormat) -> Result<String, Error> {
+    /// The deciphered url is checked as well, so a url which would be forbidden or throttled is
+    /// an error rather than failing once it is downloaded.
+    pub fn apply(&self, context: &Ctx, format: &VideoFormat) -> Result<DecipheredUrl, Error> {
         type QueryMap<'a> = HashMap<Cow<'a, str>, Cow<'a, str>>;
         // contains s, sp, and url
         let signature_map = format
@@ -109,24 +222,31 @@ impl Cipher {
             .as_ref()
             .map(|x| parse(x.as_bytes()).collect::<QueryMap<'_>>());
 
+        let plain_url = format.url.as_deref().map(Cow::Borrowed);
         let (url, sp, s) = if let Some(mut map) = signature_map {
-            // TODO: check if url is guaranteed to exist in signature
-            (map.remove("url"), map.remove("sp"), map.remove("s"))
+            // The url is usually part of the signature cipher, but may be given separately
+            let url = map.remove("url").or(plain_url);
+            (url, map.remove("sp"), map.remove("s"))
         } else {
-            (format.url.as_deref().map(Cow::Borrowed), None, None)
+            (plain_url, None, None)
         };
-        let url = url.ok_or(Error::Cipher("No url found in video format".to_owned()))?;
+        let url = url.ok_or_else(|| {
+            Error::Cipher(format!("no url found in video format {}", format.itag))
+        })?;
 
         let mut url = Url::parse(&url)?;
         let mut queries: QueryMap<'_> = url.query_pairs().collect();
 
-        if let Some(n) = queries.get("n") {
+        let n = queries.get("n").map(|n| n.to_string());
+        if let Some(n) = &n {
             let result = self.apply_nfunc(context, n)?;
             queries.insert(Cow::Borrowed("n"), Cow::Owned(result));
         }
 
-        if let Some(s) = s {
-            let sp = sp.unwrap_or(Cow::Borrowed("signature"));
+        let sp = s
+            .is_some()
+            .then(|| sp.unwrap_or(Cow::Borrowed("signature")));
+        if let (Some(s), Some(sp)) = (&s, &sp) {
             let result = self.apply_operations(s.as_ref())?;
             queries.insert(sp.clone(), Cow::Owned(result));
         }
@@ -135,7 +255,9 @@ impl Cipher {
             .extend_pairs(queries.iter())
             .finish();
         url.set_query(Some(&queries));
-        Ok(url.into())
+        let url = DecipheredUrl(url);
+        check_deciphered(&url, n.as_deref(), sp.as_deref())?;
+        Ok(url)
     }
 
     fn apply_operations(&self, signature: &str) -> Result<String, Error> {
@@ -146,29 +268,29 @@ impl Cipher {
 
         let mut chars: Vec<char> = signature.chars().collect();
         for op in operations {
-            match op {
-                Operation::Swap(x) => chars.swap(0, x % signature.len()),
-                Operation::Reverse() => chars.reverse(),
-                Operation::Splice(x) | Operation::Slice(x) => {
-                    chars.drain(0..*x);
-                }
-            }
+            op.apply(&mut chars);
         }
         Ok(chars.into_iter().collect())
     }
 
     fn apply_nfunc(&self, ctx: &Ctx, nparam: &str) -> Result<String, Error> {
-        let nfunc = self
-            .nfunc
-            .as_ref()
-            .ok_or(Error::Cipher("failed to extract n function!".to_owned()))?;
+        let nfunc = self.nfunc.as_ref().ok_or(Error::Cipher(
+            "n function not found, the stream would be throttled".to_owned(),
+        ))?;
+
+        let mut cache = self.n_cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(result) = cache.get(nparam) {
+            return Ok(result.clone());
+        }
 
-        let func = format!(r#"let n={nfunc};n("{nparam}")"#);
+        // Evaluated as an expression so the context can be reused without redeclaring anything
+        let func = format!(r#"({nfunc})("{nparam}")"#);
         match ctx.eval::<String, String>(func) {
             Ok(x) => {
                 if x.starts_with("enhanced_except") {
                     return Err(Error::JSEnhancedExcept);
                 }
+                cache.put(nparam.to_owned(), x.clone());
                 Ok(x)
             }
             Err(_) => Err(Error::JSExecution(ctx.catch().get().unwrap())),
@@ -176,69 +298,375 @@ impl Cipher {
     }
 }
 
-/// Find the name of the main function, which contains all signature operations.
-fn find_main(js: &str) -> Option<&str> {
-    static CANDIDATES: &[&str; 6] = &[
-        r"\b[cs]\s*&&\s*[adf]\.set\([^,]+\s*,\s*encodeURIComponent\s*\(\s*([a-zA-Z0-9$]+)\(",
-        r"\b[[:alnum:]]+\s*&&\s*[[:alnum:]]+\.set\([^,]+\s*,\s*encodeURIComponent\s*\(\s*([a-zA-Z0-9$]+)\(",
-        r"\bm=([a-zA-Z0-9$]{2,})\(decodeURIComponent\(h\.s\)\)",
-        r"\bc&&\(c=([a-zA-Z0-9$]{2,})\(decodeURIComponent\(c\)\)",
-        r#"(?:\b|[^a-zA-Z0-9$])([a-zA-Z0-9$]{2,})\s*=\s*function\(\s*a\s*\)\s*\{\s*a\s*=\s*a\.split\(\s*""\s*\)(?:;[a-zA-Z0-9$]{2}\.[a-zA-Z0-9$]{2}\(a,\d+\))?"#,
-        r#"([a-zA-Z0-9$]+)\s*=\s*function\(\s*a\s*\)\s*\{\s*a\s*=\s*a\.split\(\s*""\s*\)"#,
-    ];
-    static MAIN: Lazy<Regex> = Lazy::new(|| Regex::new(&CANDIDATES.join("|")).unwrap());
-
-    if let Some(captures) = MAIN.captures(js) {
-        for i in 1..=CANDIDATES.len() {
-            if let Some(val) = captures.get(i) {
-                return Some(val.as_str());
+/// A deciphered stream url, returned by [`crate::innertube::Innertube::decipher_format()`].
+///
+/// Provides helpers for adding playback parameters without clobbering the existing ones. Use
+/// [`Self::into_string()`] or the [`fmt::Display`] implementation to get the plain url.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecipheredUrl(Url);
+
+impl DecipheredUrl {
+    /// Restricts the url to the given inclusive byte range, replacing any existing range.
+    #[must_use]
+    pub fn with_range(self, start: u64, end: u64) -> Self {
+        self.with_param("range", &format!("{start}-{end}"))
+    }
+
+    /// Sets a query parameter on the url, such as `rn` or `rbuf`, replacing any existing value for
+    /// the same key while keeping all other parameters intact.
+    #[must_use]
+    pub fn with_param(mut self, key: &str, value: &str) -> Self {
+        let pairs: Vec<(String, String)> = self
+            .0
+            .query_pairs()
+            .filter(|(k, _)| k != key)
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        self.0
+            .query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair(key, value);
+        self
+    }
+
+    /// Returns the value of the given query parameter, if it exists.
+    #[must_use]
+    pub fn param(&self, key: &str) -> Option<String> {
+        self.0
+            .query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    /// Returns the url as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Converts the url into a plain string.
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0.into()
+    }
+}
+
+impl fmt::Display for DecipheredUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for DecipheredUrl {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<DecipheredUrl> for String {
+    fn from(url: DecipheredUrl) -> Self {
+        url.into_string()
+    }
+}
+
+/// A deciphered stream url along with the details needed to download it, returned by
+/// [`crate::innertube::Innertube::decipher_to_stream()`].
+///
+/// Details are taken from the query parameters of the url where possible, as these describe the
+/// stream being served, and from the [`VideoFormat`] otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecipheredStream {
+    pub url: DecipheredUrl,
+    pub itag: u32,
+    /// Mime of the format, the `mime` parameter of the url does not include the codecs.
+    pub mime: Mime,
+    /// When the url expires, from the `expire` parameter.
+    pub expires_at: Option<SystemTime>,
+    /// Size of the stream in bytes, from the `clen` parameter or
+    /// [`VideoFormat::content_length()`].
+    pub content_length: Option<u64>,
+    /// Duration of the stream, from the `dur` parameter or [`VideoFormat::approx_duration()`].
+    pub duration: Option<Duration>,
+}
+
+impl DecipheredStream {
+    pub(crate) fn new(url: DecipheredUrl, format: &VideoFormat) -> Self {
+        let expires_at = url
+            .param("expire")
+            .and_then(|x| x.parse().ok())
+            .map(|x| UNIX_EPOCH + Duration::from_secs(x));
+        let content_length = url
+            .param("clen")
+            .and_then(|x| x.parse().ok())
+            .or_else(|| format.content_length());
+        let duration = url
+            .param("dur")
+            .and_then(|x| Duration::try_from_secs_f64(x.parse().ok()?).ok())
+            .or_else(|| format.approx_duration());
+        DecipheredStream {
+            url,
+            itag: format.itag,
+            mime: format.mime_type.clone(),
+            expires_at,
+            content_length,
+            duration,
+        }
+    }
+}
+
+/// Find the body of the main function, which contains all signature operations.
+///
+/// The main function is located by its shape rather than its name, it always looks like
+/// `function(a){a=a.split("");...;return a.join("")}` although the parameter name may differ.
+/// Only the statements between the split and the join are returned.
+fn find_main_body(js: &str) -> Option<&str> {
+    for (pos, _) in js.match_indices(r#".split("")"#) {
+        let before = &js[..pos];
+        let Some(open) = before.rfind('{') else {
+            continue;
+        };
+        let Some((param, rhs)) = before[open + 1..].split_once('=') else {
+            continue;
+        };
+        if param != rhs || !is_identifier(param) {
+            continue;
+        }
+        if !before[..open].ends_with(&format!("function({param})")) {
+            continue;
+        }
+
+        let Some(block) = balanced_block(&js[open..]) else {
+            continue;
+        };
+        let body = block[1..block.len() - 1]
+            .strip_prefix(&format!(r#"{param}={param}.split("");"#))
+            .and_then(|x| x.strip_suffix(&format!(r#";return {param}.join("")"#)));
+        if body.is_some() {
+            return body;
+        }
+    }
+    None
+}
+
+/// Find the object literal assigned to the given name, including its braces.
+fn find_object<'a>(js: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{name}={{");
+    js.match_indices(&pattern)
+        .filter(|(pos, _)| {
+            // the name must not be the end of a longer name or a property
+            !matches!(
+                js[..*pos].chars().next_back(),
+                Some(x) if is_identifier_char(x) || x == '.'
+            )
+        })
+        .find_map(|(pos, _)| balanced_block(&js[pos + name.len() + 1..]))
+}
+
+/// Map the names of the functions in an object literal to their bodies.
+fn object_functions(object: &str) -> HashMap<&str, &str> {
+    let mut defs = HashMap::new();
+    let mut rest = &object[1..object.len() - 1];
+    while let Some((name, def)) = rest.split_once(':') {
+        let Some(open) = def.find('{') else {
+            break;
+        };
+        let Some(body) = balanced_block(&def[open..]) else {
+            break;
+        };
+        // Keys may also be quoted, such as "Bo":function(a,b){...}
+        let name = name.trim_matches(|x: char| matches!(x, ',' | '"' | '\'') || x.is_whitespace());
+        defs.insert(name, &body[1..body.len() - 1]);
+        rest = &def[open + body.len()..];
+    }
+    defs
+}
+
+/// Returns the block at the start of the given code, which must start with `{`, up to and
+/// including its matching `}`. Braces inside of string and regex literals and comments are
+/// skipped.
+fn balanced_block(js: &str) -> Option<&str> {
+    if !js.starts_with('{') {
+        return None;
+    }
+
+    let mut depth = 0;
+    // Last character outside of literals and comments, to tell regex literals from divisions
+    let mut prev = '{';
+    let mut chars = js.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => skip_literal(&mut chars, c),
+            '/' if chars.next_if(|&(_, x)| x == '/').is_some() => {
+                chars.find(|&(_, x)| x == '\n');
+                continue;
+            }
+            '/' if chars.next_if(|&(_, x)| x == '*').is_some() => {
+                let mut star = false;
+                chars.find(|&(_, x)| std::mem::replace(&mut star, x == '*') && x == '/');
+                continue;
+            }
+            '/' if starts_regex(&js[..pos], prev) => skip_literal(&mut chars, c),
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&js[..=pos]);
+                }
             }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            prev = c;
         }
     }
     None
 }
 
+/// Advance past the string or regex literal started by `quote`, up to its unescaped end.
+fn skip_literal(chars: &mut impl Iterator<Item = (usize, char)>, quote: char) {
+    let mut escaped = false;
+    // Slashes inside of a character class do not end a regex, such as /[/]/
+    let mut class = false;
+    for (_, c) in chars {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if quote == '/' && (c == '[' || c == ']') {
+            class = c == '[';
+        } else if c == quote && !class {
+            return;
+        }
+    }
+}
+
+/// Returns whether a `/` after the given code starts a regex literal rather than a division,
+/// which can only follow an operator or a keyword like `return`.
+fn starts_regex(before: &str, prev: char) -> bool {
+    if "(,=:[!&|?{};+-*%<>~^".contains(prev) {
+        return true;
+    }
+    let word = before
+        .trim_end()
+        .rsplit(|x| !is_identifier_char(x))
+        .next()
+        .unwrap_or_default();
+    matches!(
+        word,
+        "return" | "typeof" | "case" | "do" | "else" | "in" | "new" | "delete" | "void" | "throw"
+    )
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_identifier_char)
+}
+
 /// Extract all operations used in the main function.
 fn extract_operations(js: &str) -> Option<Vec<Operation>> {
-    const MAIN_DEF: &str =
-        r#"=function\([[:alpha:]]\)\{a=a\.split\(""\);(.*);return a\.join\(""\)}"#;
-    const FUNC_DEF: &str = r":function\(a(?:,[[:alpha:]])*\)\{(.*?)\}";
-
-    // Find the definition of the main function.
-    let main = find_main(js)?;
-    let pattern = escape(main) + MAIN_DEF;
-    let pattern = Regex::new(&pattern).unwrap();
-    let captures = pattern.captures(js)?;
-
-    // Now get the name and parameter of each operation inside.
-    // The operations look like this: Fo.Bo(3);Ho.Do(6) and so on.
-    // Note the name here is actually after the period.
-    let body = &captures[1];
-    let iter = body
+    // The operations look like this: Xy.Bo(a,3);Xy.Do(a,6) and so on, all being functions of
+    // the same helper object.
+    let body = find_main_body(js)?;
+    let calls = body
         .split(';')
-        .map(|s| (between(s, ".", "("), between(s, ",", ")")));
-
-    // Map the function names to their definitions.
-    let names = iter
-        .clone()
-        .map(|(n, _)| n)
-        .collect::<Vec<&str>>()
-        .join("|");
-    let pattern = format!("({names}){FUNC_DEF}");
-    let pattern = Regex::new(&pattern).unwrap();
-    let defs: HashMap<&str, &str> = pattern
-        .captures_iter(js)
-        .map(|c| (c.get(1).unwrap().as_str(), c.get(2).unwrap().as_str()))
-        .collect();
+        .map(|s| {
+            let object = s.split('.').next().unwrap_or_default();
+            (
+                object,
+                between(s, ".", "(").unwrap_or_default(),
+                between(s, ",", ")"),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Map the function names to their definitions inside the helper object.
+    let (object, _, _) = calls.first()?;
+    let defs = object_functions(find_object(js, object)?);
 
     // Convert each operation to the rust implementation.
-    iter.map(|(n, a)| Operation::new(defs.get(n).unwrap(), a))
-        .collect::<Result<Vec<Operation>, Error>>()
-        .ok()
+    calls
+        .iter()
+        .map(|&(_, n, a)| Operation::new(defs.get(n)?, a).ok())
+        .collect()
 }
 
-/// Extract the entire nfunc, this always seems to have some form of enhanced except at the end.
+/// Extract the entire nfunc.
+///
+/// The nfunc is first located through where it is called with the `n` parameter, then by its
+/// shape of splitting the parameter into an array, and finally by the enhanced except at its end.
 fn extract_nfunc(js: &str) -> Option<String> {
+    find_nfunc_name(js)
+        .and_then(|name| find_function(js, name))
+        .or_else(|| find_split_function(js))
+        .map(ToOwned::to_owned)
+        .or_else(|| extract_nfunc_enhanced_except(js))
+}
+
+/// Find the name of the nfunc from where it is called, such as `(b=a.get("n"))&&(b=Tna[0](b)`.
+/// If it is called through an array, the name is looked up in the array.
+fn find_nfunc_name(js: &str) -> Option<&str> {
+    static CALL: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"\.get\("n"\)\)&&\([[:alpha:]]=([[:word:]$]+)(?:\[(\d+)\])?\("#).unwrap()
+    });
+
+    let captures = CALL.captures(js)?;
+    let name = captures.get(1)?.as_str();
+    let Some(index) = captures.get(2) else {
+        return Some(name);
+    };
+
+    let index: usize = index.as_str().parse().ok()?;
+    let array = format!("var {name}=[");
+    let start = js.find(&array)? + array.len();
+    let end = start + js[start..].find(']')?;
+    js[start..end].split(',').nth(index).map(str::trim)
+}
+
+/// Find the definition of the function assigned to the given name, such as
+/// `function(a){...}`.
+fn find_function<'a>(js: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{name}=function(");
+    js.match_indices(&pattern)
+        .filter(|(pos, _)| {
+            !matches!(
+                js[..*pos].chars().next_back(),
+                Some(x) if is_identifier_char(x) || x == '.'
+            )
+        })
+        .find_map(|(pos, _)| {
+            let def = &js[pos + name.len() + 1..];
+            let open = def.find('{')?;
+            Some(&def[..open + balanced_block(&def[open..])?.len()])
+        })
+}
+
+/// Find the nfunc by its shape, it starts by splitting its parameter into an array such as
+/// `function(a){var b=a.split(""),...}`. Other functions of the same shape are much shorter, so
+/// the longest one is used.
+fn find_split_function(js: &str) -> Option<&str> {
+    static SPLIT: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"function\(([[:word:]$]+)\)\{var [[:word:]$]+=([[:word:]$]+)\.split\(""\)"#)
+            .unwrap()
+    });
+
+    SPLIT
+        .captures_iter(js)
+        .filter(|c| c[1] == c[2])
+        .filter_map(|c| {
+            let def = &js[c.get(0)?.start()..];
+            let open = def.find('{')?;
+            Some(&def[..open + balanced_block(&def[open..])?.len()])
+        })
+        .max_by_key(|def| def.len())
+}
+
+/// Extract the nfunc by the enhanced except at its end.
+fn extract_nfunc_enhanced_except(js: &str) -> Option<String> {
     static NFUNC: &str = r#"(?xs)
         function\((?P<args>[^)]*)\)\s*
         (?P<code>
@@ -267,9 +695,413 @@ fn extract_nfunc(js: &str) -> Option<String> {
     }
 }
 
+/// Checks that the signature was added to the url and that the n parameter was transformed, given
+/// the original n parameter and the name of the signature parameter.
+fn check_deciphered(url: &DecipheredUrl, n: Option<&str>, sp: Option<&str>) -> Result<(), Error> {
+    if let Some(sp) = sp {
+        if url.param(sp).unwrap_or_default().is_empty() {
+            return Err(Error::Cipher(format!(
+                "deciphered url is missing the {sp} parameter, the stream would be forbidden"
+            )));
+        }
+    }
+    if let Some(n) = n {
+        if !matches!(url.param("n"), Some(x) if x != n) {
+            return Err(Error::Cipher(
+                "n parameter was not transformed, the stream would be throttled".to_owned(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn extract_timestamp(js: &str) -> Option<String> {
     static TIMESTAMP: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"(?:signatureTimestamp|sts):(\d+)").unwrap());
     let captures = TIMESTAMP.captures(js)?;
     Some(captures[1].to_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use rquickjs::{context::EvalOptions, Context, Function, Runtime};
+
+    use super::*;
+    use crate::structs::Video;
+
+    const PLAYER_JS: &str = include_str!("../tests/fixtures/player.js");
+
+    fn deciphered(url: &str) -> DecipheredUrl {
+        DecipheredUrl(Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_with_range() {
+        let url = deciphered("https://example.googlevideo.com/videoplayback?itag=251&n=abc")
+            .with_range(0, 1023);
+        assert_eq!(
+            url.as_str(),
+            "https://example.googlevideo.com/videoplayback?itag=251&n=abc&range=0-1023"
+        );
+    }
+
+    #[test]
+    fn test_with_range_replaces() {
+        let url = deciphered("https://example.googlevideo.com/videoplayback?range=0-1&itag=251")
+            .with_range(10, 20)
+            .with_param("rn", "1");
+        assert_eq!(url.param("range").as_deref(), Some("10-20"));
+        assert_eq!(url.param("itag").as_deref(), Some("251"));
+        assert_eq!(url.param("rn").as_deref(), Some("1"));
+        assert_eq!(url.as_str().matches("range=").count(), 1);
+    }
+
+    #[test]
+    fn test_extract_player_js() {
+        assert_eq!(
+            find_main_body(PLAYER_JS),
+            Some("Xy.Ho(a,3);Xy.Fo(a,1);Xy.Bo(a,2)")
+        );
+        assert_eq!(extract_timestamp(PLAYER_JS).as_deref(), Some("19876"));
+        assert_eq!(find_nfunc_name(PLAYER_JS), Some("Qna"));
+        assert!(extract_nfunc(PLAYER_JS)
+            .unwrap()
+            .starts_with(r#"function(a){var b=a.split(""),c=[b.length];"#));
+
+        let operations = extract_operations(PLAYER_JS).unwrap();
+        assert!(matches!(
+            operations[..],
+            [
+                Operation::Swap(Arg::ModLength(3)),
+                Operation::Reverse(),
+                Operation::Splice(Arg::Value(0), Some(Arg::Value(2)))
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_matches_player_js() {
+        // The expected results come from running the player js itself through its signature
+        // and n parameter entry points, rather than from the extracted functions
+        const ENTRY_POINTS: &str = r#"(function(s, n) {
+            var sig = {};
+            _yt_player.xo(null, "sig", s, { set: function(k, v) { sig[k] = v; } });
+            var params = { get: function() { return n; }, set: function(k, v) { n = v; } };
+            _yt_player.Zo(params);
+            return [decodeURIComponent(sig.sig), n];
+        })"#;
+
+        let cipher = Cipher::new(PLAYER_JS);
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        context.with(|ctx| {
+            // The player js is not strict mode code, it assigns undeclared variables
+            let mut options = EvalOptions::default();
+            options.strict = false;
+            ctx.eval_with_options::<(), _>(PLAYER_JS, options).unwrap();
+            let entry_points: Function = ctx.eval(ENTRY_POINTS).unwrap();
+            for (s, n) in [
+                ("abcdefghijklmnopqrstuvwxyz", "aBcDeFgHiJkLmN"),
+                ("AOq0QJ8wRQIgL2CGt9W5qU1u9g5Hm9Zq=", "0123456789_-"),
+                ("xyz", "n"),
+            ] {
+                let expected: Vec<String> = entry_points.call((s, n)).unwrap();
+                assert_eq!(cipher.apply_operations(s).unwrap(), expected[0], "{s}");
+                assert_eq!(cipher.apply_nfunc(&ctx, n).unwrap(), expected[1], "{n}");
+            }
+        });
+    }
+
+    #[test]
+    fn test_extract_operations_variants() {
+        // older players declared the main function with var and a different parameter name
+        let js = r#"var Xy={Bo:function(a,b){a.splice(0,b)},Fo:function(a){a.reverse()}};
+var Wpa=function(J){J=J.split("");Xy.Fo(J,1);Xy.Bo(J,2);return J.join("")};"#;
+        assert!(matches!(
+            extract_operations(js).unwrap()[..],
+            [
+                Operation::Reverse(),
+                Operation::Splice(Arg::Value(0), Some(Arg::Value(2)))
+            ]
+        ));
+
+        // unrelated objects with the same function names, and nested braces in the helpers
+        let js = r#"var Ab={Bo:function(a){return{b:a}},Fo:function(a,b){if(b){a.push("}")}}};
+var Xy={Bo:function(a,b){a.splice(0,b)},
+Fo:function(a){if(a){a.reverse()}},
+Ho:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Wpa=function(a){a=a.split("");Xy.Bo(a,1);Xy.Ho(a,42);Xy.Fo(a,0);return a.join("")};"#;
+        assert!(matches!(
+            extract_operations(js).unwrap()[..],
+            [
+                Operation::Splice(Arg::Value(0), Some(Arg::Value(1))),
+                Operation::Swap(Arg::ModLength(42)),
+                Operation::Reverse()
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_object_functions() {
+        let defs = object_functions(
+            r#"{"Bo":function(a,b){a.splice(0,b)},'Fo':function(a){a.reverse()},
+Ho:function(a){a=a.replace(/}/g,"")}}"#,
+        );
+        assert_eq!(defs.len(), 3);
+        assert_eq!(defs["Bo"], "a.splice(0,b)");
+        assert_eq!(defs["Fo"], "a.reverse()");
+        assert_eq!(defs["Ho"], r#"a=a.replace(/}/g,"")"#);
+    }
+
+    #[test]
+    fn test_operations() {
+        let vectors = [
+            ("a.reverse()", "0", "abcdef", "fedcba"),
+            ("a.splice(0,b)", "2", "abcdef", "cdef"),
+            ("a.splice(0,b)", "10", "abc", ""),
+            ("a.splice(b,1)", "2", "abcdef", "abdef"),
+            ("a.splice(b)", "4", "abcdef", "abcd"),
+            ("a.splice(-2,1)", "0", "abcdef", "abcdf"),
+            ("a.splice(b%a.length,2)", "7", "abcdef", "adef"),
+            ("return a.slice(b)", "2", "abcdef", "cdef"),
+            ("return a.slice(-b)", "2", "abcdef", "ef"),
+            ("return a.slice(1,-1)", "0", "abcdef", "bcde"),
+            ("return a.slice(4,2)", "0", "abcdef", ""),
+            (
+                "var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c",
+                "8",
+                "abcdef",
+                "cbadef",
+            ),
+            ("var c=a[0];a[0]=a[b];a[b]=c", "9", "abcdef", "abcdef"),
+            ("var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c", "3", "", ""),
+            ("a.splice(b,1)", "3", "", ""),
+        ];
+        for (def, param, input, expected) in vectors {
+            let mut chars: Vec<char> = input.chars().collect();
+            Operation::new(def, Some(param)).unwrap().apply(&mut chars);
+            assert_eq!(chars.into_iter().collect::<String>(), expected, "{def}");
+        }
+
+        assert!(Operation::new("a.splice(0,c.d)", Some("1")).is_err());
+        assert!(Operation::new("a.push(b)", Some("1")).is_err());
+
+        // the parameter is only required if it is used
+        assert_eq!(
+            Operation::new("a.reverse()", None).unwrap(),
+            Operation::Reverse()
+        );
+        assert!(Operation::new("a.splice(0,b)", None).is_err());
+        assert!(Operation::new("a.splice(0,b)", Some("x")).is_err());
+        assert_eq!(
+            Operation::new("a.splice(0,2)", None).unwrap(),
+            Operation::Splice(Arg::Value(0), Some(Arg::Value(2)))
+        );
+    }
+
+    #[test]
+    fn test_balanced_block() {
+        assert_eq!(balanced_block("{a{b}c}d}"), Some("{a{b}c}"));
+        assert_eq!(balanced_block(r#"{a="}\"}"}b"#), Some(r#"{a="}\"}"}"#));
+        assert_eq!(balanced_block("{a{b}"), None);
+
+        // regex literals and comments are skipped, divisions are not regexes
+        assert_eq!(
+            balanced_block(r#"{a=a.replace(/[}\/]/g,"");return/}/.test(a)}b"#),
+            Some(r#"{a=a.replace(/[}\/]/g,"");return/}/.test(a)}"#)
+        );
+        assert_eq!(
+            balanced_block("{a=b/c;d=(e)/f/g;{}}h}"),
+            Some("{a=b/c;d=(e)/f/g;{}}")
+        );
+        assert_eq!(
+            balanced_block("{a=1;// }\n/* } */b=2}c"),
+            Some("{a=1;// }\n/* } */b=2}")
+        );
+        assert_eq!(balanced_block("a{b}"), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        let video: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap();
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter()
+            .find(|x| x.itag == 248)
+            .unwrap();
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        let url = context
+            .with(|ctx| Cipher::new(PLAYER_JS).apply(&ctx, format))
+            .unwrap();
+        assert_eq!(
+            url.param("sig").as_deref(),
+            Some("qAOq0QJ8wRQIgL2CGt9W5qU1u9g5Hm9ZqxNrY2ZbIr5Tk8tWqB8EmHzAiAvJSfMrhUgZ2SB3zFmG3Qo3DjUJs6FUt2WlXhvPdxH=g=A")
+        );
+        assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+        assert_eq!(url.param("itag").as_deref(), Some("248"));
+    }
+
+    #[test]
+    fn test_apply_url_shapes() {
+        let video: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap();
+        let ciphered = video
+            .streaming_data
+            .adaptive_formats
+            .iter()
+            .find(|x| x.itag == 248)
+            .unwrap();
+        let map: HashMap<String, String> =
+            parse(ciphered.signature_cipher.as_ref().unwrap().as_bytes())
+                .into_owned()
+                .collect();
+        let without_url = Serializer::new(String::new())
+            .extend_pairs(map.iter().filter(|(k, _)| *k != "url"))
+            .finish();
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        let cipher = Cipher::new(PLAYER_JS);
+        let apply = |format: &VideoFormat| context.with(|ctx| cipher.apply(&ctx, format));
+
+        // signature cipher only
+        let url = apply(ciphered).unwrap();
+        assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+        assert_eq!(url.param("sig").map(|x| x.len()), Some(103));
+
+        // plain url only
+        let mut format = ciphered.clone();
+        format.signature_cipher = None;
+        format.url = Some(map["url"].clone());
+        let url = apply(&format).unwrap();
+        assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+        assert_eq!(url.param("sig"), None);
+
+        // signature cipher without the url, which is given separately
+        format.signature_cipher = Some(without_url);
+        let url = apply(&format).unwrap();
+        assert_eq!(url.param("itag").as_deref(), Some("248"));
+        assert_eq!(url.param("sig").map(|x| x.len()), Some(103));
+
+        format.url = None;
+        match apply(&format) {
+            Err(Error::Cipher(message)) => assert!(message.contains("248")),
+            res => panic!("expected a cipher error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_apply_n_cache() {
+        let video: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap();
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter()
+            .find(|x| x.itag == 248)
+            .unwrap();
+        let cipher = Cipher::new(PLAYER_JS);
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+
+        for _ in 0..3 {
+            let url = context.with(|ctx| cipher.apply(&ctx, format)).unwrap();
+            assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+        }
+        assert_eq!(cipher.n_cache.lock().unwrap().len(), 1);
+
+        // cached results are used without evaluating the n function again
+        cipher
+            .n_cache
+            .lock()
+            .unwrap()
+            .put("aBcDeFgHiJkLmN".to_owned(), "cached".to_owned());
+        let url = context.with(|ctx| cipher.apply(&ctx, format)).unwrap();
+        assert_eq!(url.param("n").as_deref(), Some("cached"));
+    }
+
+    #[test]
+    fn test_apply_checks_url() {
+        let video: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap();
+        let mut format = video
+            .streaming_data
+            .adaptive_formats
+            .iter()
+            .find(|x| x.itag == 248)
+            .unwrap()
+            .clone();
+
+        let runtime = Runtime::new().unwrap();
+        // a single context is shared like in Innertube
+        let context = Context::full(&runtime).unwrap();
+        let apply = |cipher: Cipher, format: &VideoFormat| {
+            let res = context.with(|ctx| cipher.apply(&ctx, format));
+            match res {
+                Err(Error::Cipher(message)) => message,
+                res => panic!("expected a cipher error, got {res:?}"),
+            }
+        };
+
+        let cipher = Cipher {
+            nfunc: None,
+            ..Cipher::new(PLAYER_JS)
+        };
+        assert!(apply(cipher, &format).contains("throttled"));
+
+        let cipher = Cipher {
+            nfunc: Some("function(a){return a}".to_owned()),
+            ..Cipher::new(PLAYER_JS)
+        };
+        assert!(apply(cipher, &format).contains("throttled"));
+
+        let cipher = format.signature_cipher.as_mut().unwrap();
+        *cipher = cipher
+            .split('&')
+            .filter(|x| !x.starts_with("s="))
+            .collect::<Vec<_>>()
+            .join("&")
+            + "&s=";
+        assert!(apply(Cipher::new(PLAYER_JS), &format).contains("sig parameter"));
+    }
+
+    #[test]
+    fn test_extract_nfunc_variants() {
+        // called directly rather than through an array, without an enhanced except
+        let js = r#"var Zo=function(a){return a};
+Rna=function(a){var b=a.split(""),c=[];if(!b.length)throw Error("{");return b.reverse().join("")};
+g.Xo=function(a){var b;(b=a.get("n"))&&(b=Rna(b),a.set("n",b))};"#;
+        assert_eq!(find_nfunc_name(js), Some("Rna"));
+        assert_eq!(
+            extract_nfunc(js).as_deref(),
+            Some(
+                r#"function(a){var b=a.split(""),c=[];if(!b.length)throw Error("{");return b.reverse().join("")}"#
+            )
+        );
+
+        // the first call site is used if there are several
+        let js = r#"var Tna=[Qna],Una=[Rna];
+g.Zo=function(a){var b;(b=a.get("n"))&&(b=Tna[0](b),a.set("n",b))};
+g.Xo=function(a){var b;(b=a.get("n"))&&(b=Una[0](b),a.set("n",b))};"#;
+        assert_eq!(find_nfunc_name(js), Some("Qna"));
+
+        // no call found, falls back to the longest function splitting its parameter
+        let js = r#"Sna=function(a){var b=a.split("");return b[0]};
+Rna=function(a){var b=a.split(""),c=[b.length];b.reverse();return b.join("")};"#;
+        assert!(extract_nfunc(js).unwrap().contains("c=[b.length]"));
+    }
+
+    #[test]
+    fn test_missing_operations() {
+        let cipher = Cipher::new("var a=1;");
+        assert!(cipher.timestamp().is_none());
+        assert!(matches!(
+            cipher.apply_operations("abc"),
+            Err(Error::Cipher(_))
+        ));
+    }
+}
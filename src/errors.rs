@@ -1,11 +1,29 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use thiserror::Error;
 
+use crate::structs::PlayabilityState;
+
 #[derive(Error, Debug)]
 pub enum Error {
     /// Reqwest-related errors such as network or redirect based.
+    #[cfg(feature = "reqwest")]
     #[error("unable to send http request")]
     Reqwest(#[from] reqwest::Error),
 
+    /// Errors of a custom [`crate::transport::HttpTransport`].
+    #[error("unable to send http request: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+
+    /// Server responded with an error status, along with the body of the response. A 403 from
+    /// YouTube often means the IP is blocked or the content is region restricted.
+    #[error("server responded with status {0}")]
+    HttpStatus(u16, String),
+
     /// Url parse errors.
     #[error("failed to parse url {0}")]
     UrlParse(#[from] url::ParseError),
@@ -30,12 +48,117 @@ pub enum Error {
     #[error("{0} is not a valid YouTube url")]
     NotYoutubeUrl(String),
 
-    /// Unable to find any information on video, it could be the Innertube api might have changed
-    /// or your IP might be banned or ratelimited.
-    #[error("failed to find any info for video")]
-    VideoInfo,
+    /// No client could fetch the video, along with why each client failed. Unlike
+    /// [`Error::Unplayable`] this is usually temporary, it could be the Innertube api might have
+    /// changed or your IP might be banned or ratelimited.
+    #[error("failed to find any info for video {id}: {}", failures_display(.failures))]
+    AllClientsFailed {
+        id: String,
+        failures: Vec<ClientFailure>,
+    },
+
+    /// Transcript could not be parsed.
+    #[cfg(feature = "transcript")]
+    #[error("unable to parse transcript: {0}")]
+    Transcript(String),
+
+    /// Response could not be deserialized.
+    #[error("unable to deserialize response")]
+    Json(#[from] serde_json::Error),
+
+    /// Video is not playable with any client, such as being private, deleted or requiring a
+    /// login. The reason is YouTube's own explanation, if given.
+    #[error("video {id} is unplayable ({status:?}): {}", .reason.as_deref().unwrap_or("no reason given"))]
+    Unplayable {
+        id: String,
+        status: PlayabilityState,
+        reason: Option<String>,
+    },
+
+    /// Video is age restricted and could not be played with any client, including the fallback
+    /// of [`crate::innertube::Config::age_restricted_fallback`] if enabled. Playing the video
+    /// requires signing in.
+    #[error("video {id} is age restricted: {}", .reason.as_deref().unwrap_or("no reason given"))]
+    AgeRestricted { id: String, reason: Option<String> },
+
+    /// Video is not available in the region of the requesting IP, along with YouTube's
+    /// explanation. Requesting it from a different region, such as through a proxy, may work.
+    #[error("video {id} is not available in this region: {reason}")]
+    GeoRestricted { id: String, reason: String },
+
+    /// Video is an upcoming live stream or premiere, which can not be played until it starts.
+    #[error("video {id} is not available yet, it is scheduled to start at {}", unix_seconds(*.scheduled_start))]
+    NotYetAvailable {
+        id: String,
+        scheduled_start: SystemTime,
+    },
 
     /// Totally unexpected errors, these should be rare and are usually from quickjs.
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
+
+impl From<Infallible> for Error {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+/// Why a client failed to fetch a video, see [`Error::AllClientsFailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientFailure {
+    /// Name of the client as sent to Innertube, such as `"WEB"`.
+    pub client_name: &'static str,
+    pub cause: ClientFailureCause,
+}
+
+impl fmt::Display for ClientFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.client_name)?;
+        match &self.cause {
+            ClientFailureCause::MissingPlayer => write!(f, "(player js unavailable)"),
+            ClientFailureCause::HttpStatus(status) => write!(f, "(status {status})"),
+            ClientFailureCause::Unplayable { status, reason } => write!(
+                f,
+                "({status:?}: {})",
+                reason.as_deref().unwrap_or("no reason given")
+            ),
+            ClientFailureCause::InvalidResponse => write!(f, "(invalid response)"),
+        }
+    }
+}
+
+/// Cause of a [`ClientFailure`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientFailureCause {
+    /// The client requires the player js, but it or its signature timestamp could not be found.
+    MissingPlayer,
+    /// Innertube responded with an error status.
+    HttpStatus(u16),
+    /// The video is not playable with this client.
+    Unplayable {
+        status: PlayabilityState,
+        reason: Option<String>,
+    },
+    /// Every response was marked as invalid, its streams would not be playable.
+    InvalidResponse,
+}
+
+fn unix_seconds(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!("unix time {secs}")
+}
+
+fn failures_display(failures: &[ClientFailure]) -> String {
+    if failures.is_empty() {
+        return "no clients configured".to_owned();
+    }
+    failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,29 +1,48 @@
 use std::{
     borrow::ToOwned,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
-use tokio::sync::Mutex;
+use tokio::{
+    sync::{Mutex, OnceCell},
+    time::sleep,
+};
 
-use reqwest::{Client, RequestBuilder};
+use http::{
+    header::{HeaderValue, RANGE},
+    HeaderMap,
+};
 
-use serde_json::{json, Map};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Map, Value};
 
-use dashmap::{mapref::one::Ref, DashMap, Entry};
+use dashmap::{mapref::one::Ref, DashMap};
 use rquickjs::{async_with, AsyncContext, AsyncRuntime};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
+use url::Url;
 
 use crate::{
-    cipher::Cipher,
+    channel::{get_channel_id, get_channel_url, ChannelBrowse, ChannelInfo, ResolveUrl},
+    cipher::{Cipher, DecipheredStream, DecipheredUrl},
     clients::{ClientConfig, ClientType},
-    errors::Error,
-    query::WebSearch,
-    structs::{Video, VideoFormat},
+    errors::{ClientFailure, ClientFailureCause, Error},
+    query::{parse_suggestions, Browse, WatchNext, WebSearch},
+    structs::{
+        ChannelSearchResult, ChapterInfo, ContinuationToken, HeatMarker, PlayabilityState,
+        PlayabilityStatus, PlayerMicroformat, PlaylistInfo, PlaylistSearchResult, RelatedVideo,
+        ResponseContext, Video, VideoFormat, VideoId,
+    },
+    transport::{HttpTransport, RateLimited},
     utils::between,
 };
+#[cfg(feature = "transcript")]
+use crate::{
+    structs::CaptionTrack,
+    transcript::{TranscriptEntry, TranscriptFormat},
+};
 
 /// Url to the player javascript, which contains information required to decipher stream url
 /// This url tends to change and should be re-fetched occasionally.
@@ -49,6 +68,10 @@ impl PlayerUrl {
         // expire one day from now, arbitrary
         self.expire_at = Instant::now() + Duration::from_secs(86400);
     }
+
+    pub fn invalidate(&mut self) {
+        self.expire_at = Instant::now();
+    }
 }
 
 /// Configuration options for the Innertube instance.
@@ -58,27 +81,264 @@ pub struct Config {
     ///
     /// Defaults to Ios and Web, same as yt-dlp.
     pub configs: Vec<ClientConfig>,
-    /// [`reqwest::Client`] to use
+    /// Transport used to send http requests, see [`HttpTransport`].
     ///
-    /// Defaults to creating a new instance.
-    pub http: Client,
+    /// Defaults to a new `reqwest::Client` if the `reqwest` feature is enabled.
+    pub http: Box<dyn HttpTransport>,
     /// How many times to retry a request before skipping the config in use. Set to 0 to disable
     /// retries. It is recommended to not set this too high.
     ///
+    /// This also applies to fetching the player used for deciphering, which is retried with an
+    /// exponential backoff on network errors and server errors.
+    ///
     /// Defaults to 3.
     pub retry_limit: i8,
+    /// Language sent with every request, this affects localized text such as titles and
+    /// descriptions.
+    ///
+    /// Player requests are always sent in English, since age and region restrictions are partly
+    /// recognized by the reason YouTube gives, see [`PlayabilityStatus::geo_restriction()`]. The
+    /// localized text of a [`Video`], such as caption track names, is therefore in English.
+    ///
+    /// Defaults to `en`.
+    pub hl: String,
+    /// Region sent with every request, this affects which videos are available.
+    ///
+    /// Defaults to `US`.
+    pub gl: String,
+    /// Whether to retry age restricted videos with [`ClientType::TvEmbedded`] if none of the
+    /// configs could play them. The formats of that client are returned in this case, which may
+    /// differ from the ones of the configs.
+    ///
+    /// Defaults to false.
+    pub age_restricted_fallback: bool,
+    /// Maximum number of requests sent per second, shared by all requests of the [`Innertube`]
+    /// instance including retries. Requests are delayed until they are allowed to be sent. Rates
+    /// which are not positive and finite are ignored, rates below one request per day are
+    /// raised to that.
+    ///
+    /// Defaults to `None`, no limit.
+    pub requests_per_second: Option<f64>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
+impl Config {
+    /// Creates a config using the given transport, with all other options set to their defaults.
+    ///
+    /// Every `reqwest::Client` has its own connection pool, so pass a clone of the same client
+    /// when creating multiple [`Innertube`] instances. The pool is configured when building the
+    /// client:
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use yinfo::{Config, Innertube, Error};
+    /// # #[cfg(feature = "reqwest")]
+    /// # fn run() -> Result<(), Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .pool_max_idle_per_host(4)
+    ///     .pool_idle_timeout(Duration::from_secs(30))
+    ///     .build()?;
+    /// let first = Innertube::new(Config::with_client(client.clone()))?;
+    /// let second = Innertube::new(Config::with_client(client))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_client(http: impl HttpTransport + 'static) -> Self {
+        Config::with_transport(Box::new(http))
+    }
+
+    /// Creates a config using a new `reqwest::Client`, keeping at most `max_idle_per_host` idle
+    /// connections per host for up to `idle_timeout`. All other options are set to their
+    /// defaults.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the client fails to build.
+    #[cfg(feature = "reqwest")]
+    pub fn with_pool(max_idle_per_host: usize, idle_timeout: Duration) -> Result<Self, Error> {
+        let client = pooled_client(Some(max_idle_per_host), Some(idle_timeout))?;
+        Ok(Config::with_client(client))
+    }
+
+    fn with_transport(http: Box<dyn HttpTransport>) -> Self {
         Config {
             configs: vec![
                 ClientConfig::new(ClientType::Ios),
                 ClientConfig::new(ClientType::Web),
             ],
-            http: Client::new(),
+            http,
             retry_limit: 3,
+            hl: "en".to_owned(),
+            gl: "US".to_owned(),
+            age_restricted_fallback: false,
+            requests_per_second: None,
+        }
+    }
+}
+
+/// Builds a `reqwest::Client` with the given pool options, or reqwest's defaults where unset.
+#[cfg(feature = "reqwest")]
+fn pooled_client(
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(max) = max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(timeout) = idle_timeout {
+        builder = builder.pool_idle_timeout(timeout);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for Config {
+    fn default() -> Self {
+        Config::with_client(reqwest::Client::new())
+    }
+}
+
+/// Builder for [`Innertube`], created with [`Innertube::builder()`].
+///
+/// Options which are not set keep the defaults of [`Config`].
+///
+/// ```no_run
+/// # use yinfo::{ClientType, Innertube, Error};
+/// # fn run() -> Result<(), Error> {
+/// let innertube = Innertube::builder()
+///     .client(ClientType::Ios)
+///     .client(ClientType::Web)
+///     .retry_limit(2)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct InnertubeBuilder {
+    configs: Vec<ClientConfig>,
+    http: Option<Box<dyn HttpTransport>>,
+    retry_limit: Option<i8>,
+    hl: Option<String>,
+    gl: Option<String>,
+    age_restricted_fallback: bool,
+    requests_per_second: Option<f64>,
+    #[cfg(feature = "reqwest")]
+    pool_max_idle_per_host: Option<usize>,
+    #[cfg(feature = "reqwest")]
+    pool_idle_timeout: Option<Duration>,
+}
+
+impl InnertubeBuilder {
+    /// Adds a client of the given type, clients are used in the order they are added. See
+    /// [`Config::configs`].
+    #[must_use]
+    pub fn client(self, client_type: ClientType) -> Self {
+        self.client_config(ClientConfig::new(client_type))
+    }
+
+    /// Same as [`Self::client()`] but adds the given config, such as one with a custom embed url.
+    #[must_use]
+    pub fn client_config(mut self, config: ClientConfig) -> Self {
+        self.configs.push(config);
+        self
+    }
+
+    /// Sets the transport used to send http requests, see [`Config::http`].
+    #[must_use]
+    pub fn http(mut self, http: impl HttpTransport + 'static) -> Self {
+        self.http = Some(Box::new(http));
+        self
+    }
+
+    /// Sets how many times requests are retried, see [`Config::retry_limit`].
+    #[must_use]
+    pub fn retry_limit(mut self, retry_limit: i8) -> Self {
+        self.retry_limit = Some(retry_limit);
+        self
+    }
+
+    /// Sets the language sent with every request, see [`Config::hl`].
+    #[must_use]
+    pub fn hl(mut self, hl: &str) -> Self {
+        self.hl = Some(hl.to_owned());
+        self
+    }
+
+    /// Sets the region sent with every request, see [`Config::gl`].
+    #[must_use]
+    pub fn gl(mut self, gl: &str) -> Self {
+        self.gl = Some(gl.to_owned());
+        self
+    }
+
+    /// Sets whether to retry age restricted videos with another client, see
+    /// [`Config::age_restricted_fallback`].
+    #[must_use]
+    pub fn age_restricted_fallback(mut self, enabled: bool) -> Self {
+        self.age_restricted_fallback = enabled;
+        self
+    }
+
+    /// Limits how many requests are sent per second, see [`Config::requests_per_second`].
+    #[must_use]
+    pub fn requests_per_second(mut self, rate: f64) -> Self {
+        self.requests_per_second = Some(rate);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host by the default
+    /// `reqwest::Client`. Ignored if a transport is set with [`Self::http()`].
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long idle connections of the default `reqwest::Client` are kept open. Ignored if
+    /// a transport is set with [`Self::http()`].
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Creates the [`Innertube`] instance.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if no transport was set and the `reqwest` feature is disabled, if the
+    /// default client fails to build, or if the quickjs runtime fails to initialize.
+    pub fn build(self) -> Result<Innertube, Error> {
+        let http = match self.http {
+            Some(http) => http,
+            #[cfg(feature = "reqwest")]
+            None => Box::new(pooled_client(
+                self.pool_max_idle_per_host,
+                self.pool_idle_timeout,
+            )?),
+            #[cfg(not(feature = "reqwest"))]
+            None => return Err(Error::Transport("no http transport was set".into())),
+        };
+
+        let mut config = Config::with_transport(http);
+        if !self.configs.is_empty() {
+            config.configs = self.configs;
+        }
+        if let Some(retry_limit) = self.retry_limit {
+            config.retry_limit = retry_limit;
+        }
+        if let Some(hl) = self.hl {
+            config.hl = hl;
         }
+        if let Some(gl) = self.gl {
+            config.gl = gl;
+        }
+        config.age_restricted_fallback = self.age_restricted_fallback;
+        config.requests_per_second = self.requests_per_second;
+        Innertube::new(config)
     }
 }
 
@@ -89,16 +349,25 @@ impl Default for Config {
 pub struct Innertube {
     configs: Vec<ClientConfig>,
     web_config: ClientConfig,
+    age_fallback: Option<ClientConfig>,
 
-    http: Client,
+    http: Box<dyn HttpTransport>,
     retry_limit: i8,
     player_url: Arc<Mutex<PlayerUrl>>,
     cipher_cache: DashMap<String, Cipher>,
 
     js_runtime: AsyncRuntime,
+    /// Created on first use and shared by all deciphers, as creating a context is expensive.
+    js_context: OnceCell<AsyncContext>,
 }
 
 impl Innertube {
+    /// Creates a builder for configuring an Innertube instance, see [`InnertubeBuilder`].
+    #[must_use]
+    pub fn builder() -> InnertubeBuilder {
+        InnertubeBuilder::default()
+    }
+
     /// Creates a new Innertube instance using the given config.
     ///
     /// # Errors
@@ -107,34 +376,51 @@ impl Innertube {
     pub fn new(config: Config) -> Result<Self, Error> {
         let js_runtime = AsyncRuntime::new().map_err(|e| Error::Unexpected(e.to_string()))?;
 
+        let (hl, gl) = (config.hl, config.gl);
+        let http = match config.requests_per_second {
+            Some(rate) => RateLimited::wrap(config.http, rate),
+            None => config.http,
+        };
         Ok(Innertube {
-            http: config.http,
-            configs: config.configs,
+            http,
+            configs: config
+                .configs
+                .into_iter()
+                .map(|x| x.with_locale(&hl, &gl))
+                .collect(),
             retry_limit: config.retry_limit,
 
             player_url: Arc::new(Mutex::new(PlayerUrl::new())),
             cipher_cache: DashMap::new(),
-            web_config: ClientConfig::new(ClientType::Web),
+            web_config: ClientConfig::new(ClientType::Web).with_locale(&hl, &gl),
+            age_fallback: config
+                .age_restricted_fallback
+                .then(|| ClientConfig::new(ClientType::TvEmbedded).with_locale(&hl, &gl)),
 
             js_runtime,
+            js_context: OnceCell::new(),
         })
     }
 
     /// Deciphers a [`VideoFormat`] stream url. For some clients, Innertube responds
     /// with a ciphered url, making it is impossible to send a request unless it is deciphered.
     ///
-    /// See [`Cipher`] for more information.
+    /// See [`Cipher`] for more information. The returned [`DecipheredUrl`] can be used as a plain
+    /// string or extended with playback parameters such as a byte range.
     ///
     /// # Errors
     ///
     /// This may fail if network requests or deserialization fails, the url can not be deciphered
     /// or a quickjs context could not be created (unexpected).
-    pub async fn decipher_format(&self, format: &VideoFormat) -> Result<String, Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(itag = format.itag)))]
+    pub async fn decipher_format(&self, format: &VideoFormat) -> Result<DecipheredUrl, Error> {
         // TODO: handle cases where a player_js is not required
         let player_url = self.get_player_url().await?;
         let pair = self.get_cipher_pair(&player_url).await?;
 
-        let context = AsyncContext::full(&self.js_runtime)
+        let context = self
+            .js_context
+            .get_or_try_init(|| AsyncContext::full(&self.js_runtime))
             .await
             .map_err(|e| Error::Unexpected(e.to_string()))?;
 
@@ -144,7 +430,49 @@ impl Innertube {
         .await
     }
 
-    /// Fetches information about a video, accepting either a valid url or video id.
+    /// Same as [`Self::decipher_format()`] but returns the url along with its expiry, content
+    /// length and other details, see [`DecipheredStream`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::decipher_format()`].
+    pub async fn decipher_to_stream(
+        &self,
+        format: &VideoFormat,
+    ) -> Result<DecipheredStream, Error> {
+        let url = self.decipher_format(format).await?;
+        Ok(DecipheredStream::new(url, format))
+    }
+
+    /// Same as [`Self::decipher_format()`] but verifies the deciphered url actually works.
+    ///
+    /// YouTube may rotate the player js before the cached one expires, in which case the
+    /// deciphered url results in a 403 error. A cheap ranged request is sent to the url and on a
+    /// 403 the cached player js and cipher are discarded, a fresh one is fetched, and the format is
+    /// deciphered once more.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::decipher_format()`], in addition to failing if the verification request
+    /// could not be sent.
+    pub async fn decipher_format_verified(
+        &self,
+        format: &VideoFormat,
+    ) -> Result<DecipheredUrl, Error> {
+        let url = self.decipher_format(format).await?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, HeaderValue::from_static("bytes=0-0"));
+        let status = self.http.get_text(url.as_str(), headers).await?.status;
+        if status != 403 {
+            return Ok(url);
+        }
+
+        self.invalidate_player().await;
+        self.decipher_format(format).await
+    }
+
+    /// Fetches information about a video, accepting either a valid url, video id or [`VideoId`].
     ///
     /// If a request fails, it is retried according to the configured retry limit.
     /// And if the request still fails, the next [`ClientConfig`] is used.
@@ -154,15 +482,139 @@ impl Innertube {
     ///
     /// # Errors
     ///
-    /// This may fail if network requests or deserialization fails, no video info could be found,
-    /// or the url is not valid.
-    pub async fn info(&self, video: &str) -> Result<Video, Error> {
-        let video = get_video_id(video).ok_or(Error::NotYoutubeUrl(video.to_owned()))?;
+    /// This may fail if network requests or deserialization fails, or the url is not valid. If
+    /// the video is not playable with any client, [`Error::Unplayable`] is returned with
+    /// YouTube's reason, or [`Error::AgeRestricted`] and [`Error::GeoRestricted`] if the video
+    /// requires confirming the age or is not available in the region. Upcoming live streams and
+    /// premieres return [`Error::NotYetAvailable`] with their scheduled start.
+    /// Otherwise if no client could fetch the video, such as due to error statuses,
+    /// [`Error::AllClientsFailed`] is returned with why each client failed.
+    ///
+    /// Age restricted videos are retried with the embedded clients of the configs first, since
+    /// they can often bypass the age check, followed by [`ClientType::TvEmbedded`] if
+    /// [`Config::age_restricted_fallback`] is enabled.
+    pub async fn info<V>(&self, video: V) -> Result<Video, Error>
+    where
+        V: TryInto<VideoId>,
+        Error: From<V::Error>,
+    {
+        Ok(self.info_with_raw(video).await?.0)
+    }
+
+    /// Same as [`Self::info()`] but also returns the json response the [`Video`] was parsed
+    /// from, giving access to fields which are not part of [`Video`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::info()`].
+    pub async fn info_with_raw<V>(&self, video: V) -> Result<(Video, Value), Error>
+    where
+        V: TryInto<VideoId>,
+        Error: From<V::Error>,
+    {
+        let video = video.try_into()?;
+        let configs = self.configs.iter().collect();
+        let res = self
+            .info_with_configs(video.as_str(), configs, self.age_fallback.as_ref())
+            .await?;
+        Ok((parse_video(&res)?, res))
+    }
+
+    /// Same as [`Self::info()`] but returns the json response without parsing it into a
+    /// [`Video`], for fields which are not modelled yet. The same clients and retries are used.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::info()`], except the response is not deserialized into a [`Video`].
+    pub async fn info_raw<V>(&self, video: V) -> Result<Value, Error>
+    where
+        V: TryInto<VideoId>,
+        Error: From<V::Error>,
+    {
+        let video = video.try_into()?;
+        let configs = self.configs.iter().collect();
+        self.info_with_configs(video.as_str(), configs, self.age_fallback.as_ref())
+            .await
+    }
+
+    /// Same as [`Self::info()`] but only uses the given client, bypassing [`Config::configs`].
+    /// Useful if a video is known to require a specific client, or to compare clients.
+    ///
+    /// The client is used as given, so the locale of [`Config::hl`] and [`Config::gl`] is not
+    /// applied to it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::info()`].
+    pub async fn info_with_client<V>(&self, video: V, client: &ClientConfig) -> Result<Video, Error>
+    where
+        V: TryInto<VideoId>,
+        Error: From<V::Error>,
+    {
+        let video = video.try_into()?;
+        parse_video(
+            &self
+                .info_with_configs(video.as_str(), vec![client], None)
+                .await?,
+        )
+    }
 
-        for config in &self.configs {
+    /// Same as [`Self::info()`] but uses the embedded web player, as if the video were embedded on
+    /// the page at `embed_url`, defaulting to `https://www.youtube.com/`.
+    ///
+    /// Some videos which are blocked in the regular player can still be played when embedded,
+    /// or only when embedded on specific sites.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::info()`].
+    pub async fn embed_info<V>(&self, video: V, embed_url: Option<&str>) -> Result<Video, Error>
+    where
+        V: TryInto<VideoId>,
+        Error: From<V::Error>,
+    {
+        let video = video.try_into()?;
+        let mut client = ClientConfig::new(ClientType::WebEmbedded)
+            .with_locale(self.web_config.hl(), self.web_config.gl());
+        if let Some(url) = embed_url {
+            client = client.with_embed_url(url);
+        }
+        parse_video(
+            &self
+                .info_with_configs(video.as_str(), vec![&client], None)
+                .await?,
+        )
+    }
+
+    /// Fetches the video using the given configs in order, retrying each one according to the
+    /// retry limit. Returns the first response of a playable and valid video.
+    ///
+    /// The fallback is appended to the configs once the video is found to be age restricted.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(video_id = video, client_name, attempt))
+    )]
+    async fn info_with_configs<'a>(
+        &self,
+        video: &str,
+        mut configs: Vec<&'a ClientConfig>,
+        fallback: Option<&'a ClientConfig>,
+    ) -> Result<Value, Error> {
+        let mut failures = Vec::new();
+        let mut age_restricted = false;
+        let mut geo_restriction = None;
+        let mut scheduled_start = None;
+        let mut next = 0;
+        while let Some(&config) = configs.get(next) {
+            next += 1;
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("client_name", config.client_name());
             let mut data = Map::new();
             data.insert("videoId".to_owned(), video.into());
-            data.insert("context".to_owned(), config.context_json());
+            // The reasons of unplayable videos are only recognized in English, see Config::hl
+            let mut context = config.context_json();
+            context["client"]["hl"] = "en".into();
+            data.insert("context".to_owned(), context);
             data.insert("contentCheckOk".to_owned(), true.into());
             data.insert("racyCheckOk".to_owned(), true.into());
 
@@ -170,6 +622,10 @@ impl Innertube {
                 let player_url = self.get_player_url().await?;
                 // Nothing we can do besides using the next config
                 if player_url.is_empty() {
+                    failures.push(ClientFailure {
+                        client_name: config.client_name(),
+                        cause: ClientFailureCause::MissingPlayer,
+                    });
                     continue;
                 }
 
@@ -185,26 +641,119 @@ impl Innertube {
                         }),
                     );
                 } else {
+                    failures.push(ClientFailure {
+                        client_name: config.client_name(),
+                        cause: ClientFailureCause::MissingPlayer,
+                    });
                     continue;
                 }
             }
 
-            // TODO: also retry on http error?
             let data = data.into();
-            for _attempt in 0..=self.retry_limit {
-                let res = self
-                    .build_request("player", config, &data)
-                    .send()
-                    .await?
-                    .json::<Video>()
-                    .await?;
-
-                if !video_invalid(&res) {
+            let mut cause = ClientFailureCause::InvalidResponse;
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            for attempt in 0..=self.retry_limit {
+                #[cfg(feature = "tracing")]
+                tracing::Span::current().record("attempt", attempt);
+                let res: Value = match self.post("player", config, &data).await {
+                    Ok(res) => res,
+                    Err(Error::HttpStatus(status, _)) => {
+                        cause = ClientFailureCause::HttpStatus(status);
+                        break;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                // Other clients may still be able to play the video, e.g. if it is age restricted
+                let status = PlayabilityStatus::deserialize(&res["playabilityStatus"])?;
+                if status.is_age_restricted() {
+                    // Embedded clients can often bypass the age check, so try them first
+                    configs[next..].sort_by_key(|config| !config.is_embed());
+                    if let Some(fallback) = fallback.filter(|_| !age_restricted) {
+                        configs.push(fallback);
+                    }
+                    age_restricted = true;
+                }
+                if let Some(reason) = status.geo_restriction() {
+                    geo_restriction = Some(reason);
+                }
+                if let Some(time) = status.scheduled_start_time() {
+                    scheduled_start = Some(time);
+                }
+                if status.status != PlayabilityState::Ok {
+                    cause = ClientFailureCause::Unplayable {
+                        reason: status.reason(),
+                        status: status.status,
+                    };
+                    break;
+                }
+
+                let context = ResponseContext::deserialize(&res["responseContext"])?;
+                if !video_invalid(&context) {
                     return Ok(res);
                 }
             }
+            failures.push(ClientFailure {
+                client_name: config.client_name(),
+                cause,
+            });
+        }
+
+        // The video itself is unplayable if every client says so, rather than only some failing
+        let unplayable =
+            |x: &ClientFailure| matches!(x.cause, ClientFailureCause::Unplayable { .. });
+        if !failures.is_empty() && failures.iter().all(unplayable) {
+            if let Some(ClientFailureCause::Unplayable { status, reason }) =
+                failures.pop().map(|x| x.cause)
+            {
+                if let Some(scheduled_start) = scheduled_start {
+                    return Err(Error::NotYetAvailable {
+                        id: video.to_owned(),
+                        scheduled_start,
+                    });
+                }
+                // The region is decided by the IP, so no client can bypass it
+                if let Some(reason) = geo_restriction {
+                    return Err(Error::GeoRestricted {
+                        id: video.to_owned(),
+                        reason,
+                    });
+                }
+                if age_restricted {
+                    return Err(Error::AgeRestricted {
+                        id: video.to_owned(),
+                        reason,
+                    });
+                }
+                return Err(Error::Unplayable {
+                    id: video.to_owned(),
+                    status,
+                    reason,
+                });
+            }
+        }
+        Err(Error::AllClientsFailed {
+            id: video.to_owned(),
+            failures,
+        })
+    }
+
+    /// Fetches the video again if its format urls have expired, otherwise returns it unchanged.
+    /// See [`Video::formats_expire_at()`].
+    ///
+    /// Videos with an unknown expiry are always fetched again.
+    ///
+    /// # Errors
+    ///
+    /// This may fail for the same reasons as [`Self::info()`].
+    pub async fn refresh(&self, video: Video) -> Result<Video, Error> {
+        if video
+            .formats_expire_at()
+            .is_some_and(|expiry| expiry > SystemTime::now())
+        {
+            return Ok(video);
         }
-        Err(Error::VideoInfo)
+        self.info(&video.video_details.video_id).await
     }
 
     /// Fetches search results for the given query, returning a list of video ids.
@@ -216,46 +765,414 @@ impl Innertube {
     ///
     /// This may fail if network requests or deserialization fails.
     pub async fn search(&self, query: &str) -> Result<Vec<String>, Error> {
-        // Params are to indicate videos only
+        self.search_with_client(query, &self.web_config).await
+    }
+
+    /// Same as [`Self::search()`] but sends the request with the given client.
+    ///
+    /// The response is parsed as a web search response, clients responding with a different
+    /// layout, such as the mobile ones, return no results.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::search()`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, client), fields(client_name = client.client_name()))
+    )]
+    pub async fn search_with_client(
+        &self,
+        query: &str,
+        client: &ClientConfig,
+    ) -> Result<Vec<String>, Error> {
+        Ok(self
+            .post::<WebSearch>("search", client, &search_data(query, client, SEARCH_VIDEOS))
+            .await?
+            .queries())
+    }
+
+    /// Fetches search results for the given query, returning the ids and titles of the channels
+    /// found.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails.
+    pub async fn search_channels(&self, query: &str) -> Result<Vec<ChannelSearchResult>, Error> {
+        let data = search_data(query, &self.web_config, SEARCH_CHANNELS);
+        Ok(self
+            .post::<WebSearch>("search", &self.web_config, &data)
+            .await?
+            .channels())
+    }
+
+    /// Fetches search results for the given query, returning the ids and titles of the
+    /// playlists found.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails.
+    pub async fn search_playlists(&self, query: &str) -> Result<Vec<PlaylistSearchResult>, Error> {
+        let data = search_data(query, &self.web_config, SEARCH_PLAYLISTS);
+        Ok(self
+            .post::<WebSearch>("search", &self.web_config, &data)
+            .await?
+            .playlists())
+    }
+
+    /// Same as [`Self::search()`] but returns the json response without parsing it, for fields
+    /// which are not modelled yet.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests fail or the response is not json.
+    pub async fn search_raw(&self, query: &str) -> Result<Value, Error> {
+        let data = search_data(query, &self.web_config, SEARCH_VIDEOS);
+        self.post("search", &self.web_config, &data).await
+    }
+
+    /// Fetches autocomplete suggestions for a partially typed search query.
+    ///
+    /// This uses the search completion endpoint rather than the Innertube api. Suggestions are
+    /// localized according to [`Config::hl`] and [`Config::gl`].
+    ///
+    /// # Errors
+    ///
+    /// This may fail if the network request fails, the server responds with an error status or
+    /// the response is not a list of suggestions.
+    pub async fn search_suggestions(&self, partial_query: &str) -> Result<Vec<String>, Error> {
+        let url = Url::parse_with_params(
+            "https://suggestqueries-clients6.google.com/complete/search",
+            &[
+                ("client", "youtube"),
+                ("ds", "yt"),
+                ("hl", self.web_config.hl()),
+                ("gl", self.web_config.gl()),
+                ("q", partial_query),
+            ],
+        )?;
+        let body = self.fetch_text(url.as_str()).await?;
+
+        parse_suggestions(&body)
+    }
+
+    /// Fetches metadata of a channel, accepting either a channel url or channel id.
+    ///
+    /// Urls with a handle (`/@name`), custom url (`/c/name`) or username (`/user/name`) are
+    /// resolved to the channel id first, which takes an additional request.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn channel_info(&self, channel: &str) -> Result<ChannelInfo, Error> {
+        let channel = self.resolve_channel_id(channel).await?;
+        let channel = channel.as_str();
         let data = json!({
-            "query": query,
+            "browseId": channel,
             "context": self.web_config.context_json(),
-            "params": "EgIQAfABAQ==",
         });
 
         Ok(self
-            .build_request("search", &self.web_config, &data)
-            .send()
+            .post::<ChannelBrowse>("browse", &self.web_config, &data)
             .await?
-            .json::<WebSearch>()
+            .info(channel))
+    }
+
+    /// Fetches the first page of videos uploaded by a channel, accepting either a channel url or
+    /// channel id.
+    ///
+    /// Channel urls are resolved the same way as in [`Self::channel_info()`].
+    ///
+    /// Returns the video ids along with a token for the next page, if there is one. Use
+    /// [`Self::channel_videos_next()`] to fetch the next page.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn channel_videos(
+        &self,
+        channel: &str,
+    ) -> Result<(Vec<String>, Option<ContinuationToken>), Error> {
+        let channel = self.resolve_channel_id(channel).await?;
+        let channel = channel.as_str();
+        // Params are to select the videos tab
+        let data = json!({
+            "browseId": channel,
+            "context": self.web_config.context_json(),
+            "params": "EgZ2aWRlb3M=",
+        });
+
+        let res = self.browse(&data).await?;
+        Ok((res.video_ids(), res.continuation()))
+    }
+
+    /// Fetches the next page of videos uploaded by a channel, see [`Self::channel_videos()`].
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails.
+    pub async fn channel_videos_next(
+        &self,
+        token: &ContinuationToken,
+    ) -> Result<(Vec<String>, Option<ContinuationToken>), Error> {
+        let data = json!({
+            "continuation": token.as_str(),
+            "context": self.web_config.context_json(),
+        });
+
+        let res = self.browse(&data).await?;
+        Ok((res.video_ids(), res.continuation()))
+    }
+
+    /// Fetches the metadata and first page of videos of a playlist, accepting either a playlist
+    /// url or playlist id.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn playlist_info(&self, playlist: &str) -> Result<PlaylistInfo, Error> {
+        let playlist =
+            get_playlist_id(playlist).ok_or(Error::NotYoutubeUrl(playlist.to_owned()))?;
+        let data = json!({
+            "browseId": format!("VL{playlist}"),
+            "context": self.web_config.context_json(),
+        });
+
+        Ok(self.browse(&data).await?.playlist_info(playlist))
+    }
+
+    /// Fetches the currently trending videos for the given region code, such as `"US"` or `"DE"`,
+    /// returning a list of video ids.
+    ///
+    /// An empty region defaults to `"US"`. YouTube does not reject unsupported region codes but
+    /// instead silently falls back to a default region, so the results may not be for the given
+    /// region.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails.
+    pub async fn trending(&self, region: &str) -> Result<Vec<String>, Error> {
+        let region = if region.is_empty() { "US" } else { region };
+        let mut context = self.web_config.context_json();
+        context["client"]["gl"] = region.into();
+        let data = json!({
+            "browseId": "FEtrending",
+            "context": context,
+        });
+
+        Ok(self.browse(&data).await?.video_ids())
+    }
+
+    /// Fetches the videos recommended alongside a video, accepting either a valid url or video id.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn related_videos(&self, video: &str) -> Result<Vec<RelatedVideo>, Error> {
+        Ok(self.watch_next(video).await?.related_videos())
+    }
+
+    /// Fills in the channel handle and avatar of a video from the watch page, see
+    /// [`crate::structs::VideoDetails::channel_thumbnails`]. Fields which are already set are kept.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails.
+    pub async fn channel_details(&self, video: &mut Video) -> Result<(), Error> {
+        let next = self.watch_next(&video.video_details.video_id).await?;
+        let details = &mut video.video_details;
+        if details.channel_handle.is_none() {
+            details.channel_handle = next.channel_handle();
+        }
+        if details.channel_thumbnails.is_none() {
+            details.channel_thumbnails = next.channel_thumbnails();
+        }
+        Ok(())
+    }
+
+    /// Fetches the url of the first storyboard sheet of a video, using the level with the largest
+    /// thumbnails. Returns `None` if the video has no storyboard, such as live streams.
+    ///
+    /// Use [`Video::storyboards()`] for all levels and sheets.
+    ///
+    /// # Errors
+    ///
+    /// This may fail for the same reasons as [`Self::info()`].
+    pub async fn storyboard_url(&self, video: &str) -> Result<Option<String>, Error> {
+        let storyboards = self.info(video).await?.storyboards();
+        Ok(storyboards.last().map(|level| level.sheet_url(0)))
+    }
+
+    /// Fetches the chapters of a video, accepting either a valid url or video id.
+    ///
+    /// Chapters are taken from the chapters panel of the watch page, or parsed from timestamps in
+    /// the description if the panel is missing. Returns an empty list if the video has no
+    /// chapters.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn video_chapters(&self, video: &str) -> Result<Vec<ChapterInfo>, Error> {
+        Ok(self.watch_next(video).await?.chapters())
+    }
+
+    /// Fetches the "most replayed" heatmap of a video, accepting either a valid url or video id.
+    ///
+    /// Returns an empty list if the video has no heatmap, which is the case for most videos
+    /// without many views.
+    ///
+    /// # Errors
+    ///
+    /// This may fail if network requests or deserialization fails, or the url is not valid.
+    pub async fn heatmap(&self, video: &str) -> Result<Vec<HeatMarker>, Error> {
+        Ok(self.watch_next(video).await?.heat_markers())
+    }
+
+    /// Fetches the raw DASH manifest (MPD xml) from the given url, usually
+    /// [`crate::structs::StreamData::dash_manifest_url`].
+    ///
+    /// The manifest is returned as is. Unlike the formats, stream urls inside it are not ciphered
+    /// and can be used directly, there is nothing to pass to [`Self::decipher_format()`].
+    ///
+    /// # Errors
+    ///
+    /// This may fail if the network request fails or the server responds with an error status.
+    pub async fn fetch_dash_manifest(&self, url: &str) -> Result<String, Error> {
+        self.fetch_text(url).await
+    }
+
+    /// Fetches the raw HLS playlist (m3u8) from the given url, usually
+    /// [`crate::structs::StreamData::hls_manifest_url`].
+    ///
+    /// The playlist is returned as is. Unlike the formats, stream urls inside it are not ciphered
+    /// and can be used directly, there is nothing to pass to [`Self::decipher_format()`].
+    ///
+    /// # Errors
+    ///
+    /// This may fail if the network request fails or the server responds with an error status.
+    pub async fn fetch_hls_manifest(&self, url: &str) -> Result<String, Error> {
+        self.fetch_text(url).await
+    }
+
+    /// Fetches the transcript of a caption track, see [`Video::captions()`].
+    ///
+    /// # Errors
+    ///
+    /// This may fail if the network request fails, the server responds with an error status or
+    /// the transcript could not be parsed.
+    #[cfg(feature = "transcript")]
+    pub async fn transcript(
+        &self,
+        track: &CaptionTrack,
+        format: TranscriptFormat,
+    ) -> Result<Vec<TranscriptEntry>, Error> {
+        let mut url = Url::parse(&track.base_url)?;
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(k, _)| k != "fmt")
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("fmt", format.param());
+
+        format.parse(&self.fetch_text(url.as_str()).await?)
+    }
+
+    async fn fetch_text(&self, url: &str) -> Result<String, Error> {
+        Ok(self
+            .http
+            .get_text(url, HeaderMap::new())
             .await?
-            .queries())
+            .error_for_status()?
+            .body)
+    }
+
+    /// Fetch the watch page data of a video, this contains information which is not part of the
+    /// player response.
+    async fn watch_next(&self, video: &str) -> Result<WatchNext, Error> {
+        let video = get_video_id(video).ok_or(Error::NotYoutubeUrl(video.to_owned()))?;
+        let data = json!({
+            "videoId": video,
+            "context": self.web_config.context_json(),
+        });
+
+        self.post("next", &self.web_config, &data).await
+    }
+
+    /// Get the channel id of a channel url or id. Handles, custom urls and usernames are resolved
+    /// with a request to the resolve url endpoint.
+    async fn resolve_channel_id(&self, channel: &str) -> Result<String, Error> {
+        if let Some(id) = get_channel_id(channel) {
+            return Ok(id.to_owned());
+        }
+        let url = get_channel_url(channel).ok_or(Error::NotYoutubeUrl(channel.to_owned()))?;
+        let data = json!({
+            "url": url,
+            "context": self.web_config.context_json(),
+        });
+
+        self.post::<ResolveUrl>("navigation/resolve_url", &self.web_config, &data)
+            .await?
+            .channel_id()
+            .ok_or(Error::NotYoutubeUrl(channel.to_owned()))
+    }
+
+    /// Send a request to the browse endpoint, used for listings of videos.
+    async fn browse(&self, data: &serde_json::Value) -> Result<Browse, Error> {
+        self.post("browse", &self.web_config, data).await
     }
 
     /// Return the cipher key for the given player url, creating one if it does not exist.
-    async fn get_cipher_pair(&self, player_url: &str) -> Result<Ref<String, Cipher>, Error> {
-        match self.cipher_cache.entry(player_url.to_string()) {
-            Entry::Vacant(entry) => {
-                let player_js = self.http.get(player_url).send().await?.text().await?;
-                Ok(entry.insert(Cipher::new(&player_js)).downgrade())
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    async fn get_cipher_pair(&self, player_url: &str) -> Result<Ref<'_, String, Cipher>, Error> {
+        if let Some(cipher) = self.cipher_cache.get(player_url) {
+            return Ok(cipher);
+        }
+        // Fetched without holding the entry, failures are not cached so the next call retries
+        let player_js = self.fetch_text_retried(player_url).await?;
+        Ok(self
+            .cipher_cache
+            .entry(player_url.to_owned())
+            .or_insert_with(|| Cipher::new(&player_js))
+            .downgrade())
+    }
+
+    /// Same as [`Self::fetch_text()`] but retries transient failures according to the retry
+    /// limit, waiting twice as long before each retry.
+    async fn fetch_text_retried(&self, url: &str) -> Result<String, Error> {
+        let mut backoff = RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.fetch_text(url).await {
+                Err(e) if attempt < self.retry_limit && is_transient(&e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(url, attempt, error = %e, "retrying request");
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                res => return res,
             }
-            Entry::Occupied(entry) => Ok(entry.into_ref().downgrade()),
         }
     }
 
     /// Get the currently cached player js url, making a request to fetch it if it is expired.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(player_url_cached))
+    )]
     async fn get_player_url(&self) -> Result<String, Error> {
         let mut player_url = self.player_url.lock().await;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("player_url_cached", !player_url.is_expired());
         if player_url.is_expired() {
-            let res = self
-                .http
-                .get("https://www.youtube.com/embed/")
-                .send()
-                .await?
-                .text()
+            // Errors are returned without setting the url, so they are retried on the next call
+            let body = self
+                .fetch_text_retried("https://www.youtube.com/embed/")
                 .await?;
 
-            let url = between(&res, "\"jsUrl\":\"", "\"");
+            let url = between(&body, "\"jsUrl\":\"", "\"").unwrap_or_default();
             let url = if url.starts_with("//") {
                 "https:".to_owned() + url
             } else if url.starts_with('/') {
@@ -273,24 +1190,82 @@ impl Innertube {
         Ok(player_url.url.clone())
     }
 
-    /// Build a request to be sent to the Innertube api
-    fn build_request(
+    /// Discard the cached player js url along with its cipher, forcing both to be re-fetched.
+    async fn invalidate_player(&self) {
+        let mut player_url = self.player_url.lock().await;
+        self.cipher_cache.remove(&player_url.url);
+        player_url.invalidate();
+    }
+
+    /// Send a request to the Innertube api, deserializing the response.
+    async fn post<T: DeserializeOwned>(
         &self,
         endpoint: &str,
         config: &ClientConfig,
         data: &serde_json::Value,
-    ) -> RequestBuilder {
-        let url = format!(r"https:\\{}/youtubei/v1/{}", config.hostname(), endpoint);
-        self.http
-            .post(url)
-            .headers(config.headers())
-            .query(&[("key", config.api_key()), ("prettyPrint", "false")])
-            .json(data)
+    ) -> Result<T, Error> {
+        let url = format!(
+            "https://{}/youtubei/v1/{}?key={}&prettyPrint=false",
+            config.hostname(),
+            endpoint,
+            config.api_key()
+        );
+        // Error responses usually still have a json body, which would parse as a `Value`
+        let res = self
+            .http
+            .post_json(&url, config.headers(), data)
+            .await?
+            .error_for_status()?;
+        Ok(serde_json::from_str(&res.body)?)
+    }
+}
+
+/// Delay before the first retry of [`Innertube::fetch_text_retried()`], doubled for every retry.
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Returns whether the error may go away when retrying, such as network errors or server errors.
+fn is_transient(e: &Error) -> bool {
+    match e {
+        #[cfg(feature = "reqwest")]
+        Error::Reqwest(_) => true,
+        Error::Transport(_) => true,
+        Error::HttpStatus(status, _) => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
+/// Parses a player response into a [`Video`], marking it as fetched now.
+fn parse_video(res: &Value) -> Result<Video, Error> {
+    let mut video = Video::deserialize(res)?;
+    video.fetched_at = Some(SystemTime::now());
+    if video.video_details.channel_handle.is_none() {
+        video.video_details.channel_handle = video
+            .microformat()
+            .and_then(PlayerMicroformat::channel_handle)
+            .map(str::to_owned);
     }
+    Ok(video)
+}
+
+/// Search params filtering the results to videos only.
+const SEARCH_VIDEOS: &str = "EgIQAfABAQ==";
+/// Search params filtering the results to channels only.
+const SEARCH_CHANNELS: &str = "EgIQAg==";
+/// Search params filtering the results to playlists only.
+const SEARCH_PLAYLISTS: &str = "EgIQAw==";
+
+/// Creates the request data for a search with the given client, the params select which kind of
+/// results are returned, such as [`SEARCH_VIDEOS`].
+fn search_data(query: &str, client: &ClientConfig, params: &str) -> Value {
+    json!({
+        "query": query,
+        "context": client.context_json(),
+        "params": params,
+    })
 }
 
 /// Attempt to get the video id from a YouTube url
-fn get_video_id(url: &str) -> Option<&str> {
+pub(crate) fn get_video_id(url: &str) -> Option<&str> {
     // from: https://stackoverflow.com/questions/5830387/how-do-i-find-all-youtube-video-ids-in-a-string-using-a-regex?noredirect=1&lq=1
     // this is a little lax however, but should suffice for most cases
     const YOUTUBE_URL: &str = r"(?xs)
@@ -319,12 +1294,38 @@ fn get_video_id(url: &str) -> Option<&str> {
     None
 }
 
+/// Attempt to get the playlist id from a YouTube url
+fn get_playlist_id(url: &str) -> Option<&str> {
+    const PLAYLIST_URL: &str = r"(?x)
+        (?:https?:)?//              # protocol
+        (?:[[:alnum:]-]+\.)?        # optional subdomain
+        youtube(?:-nocookie)?\.com
+        \S*?                        # whatever until we find the list parameter
+        [?&]list=
+        ([[:word:]-]+)              # id
+    ";
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(PLAYLIST_URL).unwrap());
+
+    if let Some(cap) = PATTERN.captures(url) {
+        return Some(cap.get(1).unwrap().as_str());
+    } else if ["PL", "RD", "OL", "UU", "FL", "LL"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix))
+        && url.len() > 2
+        && url
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Some(url);
+    }
+    None
+}
+
 /// Check if a video is invalid. A video is marked invalid if a set of tokens, 51217102 or
 /// 51217476 exist in the video, in which case any request results in a 403 error.
-fn video_invalid(video: &Video) -> bool {
+fn video_invalid(context: &ResponseContext) -> bool {
     // TODO: do both tokens have to exist?
-    video
-        .response_context
+    context
         .service_tracking_params
         .iter()
         .find(|service| service.service == "GFEEDBACK")
@@ -345,7 +1346,569 @@ fn video_invalid(video: &Video) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::time::UNIX_EPOCH;
+
     use super::*;
+    use crate::transport::{BoxFuture, HttpResponse};
+
+    const PLAYER_JSON: &str = include_str!("../tests/fixtures/player.json");
+    /// Body YouTube sends along with error statuses.
+    const ERROR_JSON: &str = r#"{"error": {"code": 403, "message": "The caller does not have permission", "status": "PERMISSION_DENIED"}}"#;
+
+    /// Transport serving canned responses by endpoint.
+    #[derive(Debug)]
+    struct MockTransport {
+        player: String,
+        /// Player response for the embedded TV client, if it differs from the others.
+        tv_player: Option<String>,
+        /// Client whose player requests are denied with a json error body.
+        forbidden: Option<&'static str>,
+        /// Whether the first get request to every url fails with a server error.
+        flaky: bool,
+        failed: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl MockTransport {
+        fn new() -> Self {
+            MockTransport {
+                player: PLAYER_JSON.to_owned(),
+                tv_player: None,
+                forbidden: None,
+                flaky: false,
+                failed: std::sync::Mutex::default(),
+            }
+        }
+
+        fn respond(&self, url: &str) -> Result<HttpResponse, Error> {
+            let body = if url.contains("/youtubei/v1/player") {
+                &self.player
+            } else if url.contains("/youtubei/v1/navigation/resolve_url") {
+                r#"{"endpoint": {"browseEndpoint": {"browseId": "UCuAXFkgsw1L7xaCfnd5JJOw"}}}"#
+            } else if url.contains("/youtubei/v1/browse") {
+                r#"{"metadata": {"channelMetadataRenderer": {"title": "Rick Astley", "externalId": "UCuAXFkgsw1L7xaCfnd5JJOw"}}}"#
+            } else if url.contains("complete/search") && url.contains("hl=en&gl=US") {
+                r#"window.google.ac.h(["rust",[["rust",0,[512]],["rust game",0,[512]]],{}])"#
+            } else if url.contains("/youtubei/v1/error") {
+                return Ok(HttpResponse {
+                    status: 403,
+                    body: ERROR_JSON.to_owned(),
+                });
+            } else if url.ends_with("/embed/") {
+                r#"<script>ytcfg.set({"jsUrl":"/s/player/6e1dd460/player_ias.vflset/en_US/base.js"});</script>"#
+            } else if url.ends_with("/base.js") {
+                include_str!("../tests/fixtures/player.js")
+            } else {
+                return Ok(HttpResponse {
+                    status: 404,
+                    body: String::new(),
+                });
+            };
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_owned(),
+            })
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn post_json<'a>(
+            &'a self,
+            url: &'a str,
+            _headers: HeaderMap,
+            body: &'a Value,
+        ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+            let client_name = &body["context"]["client"]["clientName"];
+            // player requests are always sent in English
+            if url.contains("/youtubei/v1/player") && body["context"]["client"]["hl"] != "en" {
+                return Box::pin(async {
+                    Ok(HttpResponse {
+                        status: 400,
+                        body: ERROR_JSON.to_owned(),
+                    })
+                });
+            }
+            if self.forbidden.is_some_and(|x| client_name == x) {
+                return Box::pin(async move {
+                    Ok(HttpResponse {
+                        status: 403,
+                        body: ERROR_JSON.to_owned(),
+                    })
+                });
+            }
+            if let Some(player) = &self.tv_player {
+                if client_name == "TVHTML5_SIMPLY_EMBEDDED_PLAYER" {
+                    return Box::pin(async move {
+                        Ok(HttpResponse {
+                            status: 200,
+                            body: player.clone(),
+                        })
+                    });
+                }
+            }
+            Box::pin(async move { self.respond(url) })
+        }
+
+        fn get_text<'a>(
+            &'a self,
+            url: &'a str,
+            _headers: HeaderMap,
+        ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+            Box::pin(async move {
+                let mut failed = self.failed.lock().unwrap();
+                if self.flaky && !failed.iter().any(|x| x == url) {
+                    failed.push(url.to_owned());
+                    return Ok(HttpResponse {
+                        status: 500,
+                        body: String::new(),
+                    });
+                }
+                drop(failed);
+                self.respond(url)
+            })
+        }
+    }
+
+    fn innertube_with(client: ClientType, http: MockTransport) -> Innertube {
+        Innertube::new(Config {
+            configs: vec![ClientConfig::new(client)],
+            retry_limit: 0,
+            ..Config::with_client(http)
+        })
+        .unwrap()
+    }
+
+    fn innertube() -> Innertube {
+        innertube_with(ClientType::Ios, MockTransport::new())
+    }
+
+    #[tokio::test]
+    async fn test_builder() {
+        let innertube = Innertube::builder()
+            .client(ClientType::Web)
+            .client_config(ClientConfig::new(ClientType::Ios))
+            .http(MockTransport::new())
+            .retry_limit(0)
+            .hl("de")
+            .gl("AT")
+            .build()
+            .unwrap();
+        assert_eq!(innertube.configs.len(), 2);
+        assert_eq!(innertube.configs[0].client_name(), "WEB");
+        assert_eq!(innertube.retry_limit, 0);
+        assert_eq!(innertube.web_config.hl(), "de");
+        assert_eq!(innertube.configs[1].gl(), "AT");
+        assert!(innertube.age_fallback.is_none());
+
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+
+        let innertube = Innertube::builder()
+            .http(MockTransport::new())
+            .age_restricted_fallback(true)
+            .build()
+            .unwrap();
+        assert_eq!(innertube.configs.len(), 2);
+        assert_eq!(innertube.retry_limit, 3);
+        assert!(innertube.age_fallback.is_some());
+
+        #[cfg(feature = "reqwest")]
+        {
+            let innertube = Innertube::builder()
+                .pool_max_idle_per_host(2)
+                .pool_idle_timeout(Duration::from_secs(10))
+                .build()
+                .unwrap();
+            assert_eq!(innertube.configs.len(), 2);
+            assert!(Config::with_pool(2, Duration::from_secs(10)).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_info_transport() {
+        let video = innertube().info("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+        assert!(video.fetched_at.is_some());
+        assert_eq!(
+            video.video_details.channel_handle.as_deref(),
+            Some("@RickAstleyYT")
+        );
+
+        // the handle is filled in but not written back into the response
+        let original: Value = serde_json::from_str(PLAYER_JSON).unwrap();
+        assert_eq!(serde_json::to_value(&video).unwrap(), original);
+    }
+
+    #[tokio::test]
+    async fn test_storyboard_url() {
+        let url = innertube().storyboard_url("dQw4w9WgXcQ").await.unwrap();
+        assert!(url
+            .unwrap()
+            .starts_with("https://i.ytimg.com/sb/dQw4w9WgXcQ/storyboard3_L2/M0.jpg?"));
+    }
+
+    #[tokio::test]
+    async fn test_info_player_js() {
+        let innertube = innertube_with(ClientType::Web, MockTransport::new());
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+
+        let player_url = innertube.get_player_url().await.unwrap();
+        assert_eq!(
+            player_url,
+            "https://www.youtube.com/s/player/6e1dd460/player_ias.vflset/en_US/base.js"
+        );
+        let pair = innertube.get_cipher_pair(&player_url).await.unwrap();
+        assert_eq!(pair.value().timestamp(), Some("19876"));
+    }
+
+    #[tokio::test]
+    async fn test_info_with_raw() {
+        let (video, raw) = innertube().info_with_raw("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(raw["videoDetails"]["videoId"], video.video_details.video_id);
+        assert_eq!(
+            raw["microformat"]["playerMicroformatRenderer"]["category"],
+            "Music"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_info_raw() {
+        let innertube = innertube();
+        let raw = innertube.info_raw("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(raw["videoDetails"]["videoId"], "dQw4w9WgXcQ");
+
+        let res = innertube.info_raw("https://example.com/").await;
+        assert!(matches!(res, Err(Error::NotYoutubeUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_info_with_client() {
+        let innertube = innertube();
+        let client = ClientConfig::new(ClientType::Web);
+        let video = innertube
+            .info_with_client("dQw4w9WgXcQ", &client)
+            .await
+            .unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+        // the web client requires the player js, which is otherwise never fetched
+        assert_eq!(innertube.cipher_cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_embed_info() {
+        let innertube = innertube();
+        let video = innertube
+            .embed_info("dQw4w9WgXcQ", Some("https://example.com/"))
+            .await
+            .unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+        // the embedded web client requires the player js
+        assert_eq!(innertube.cipher_cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_channel_info_handle() {
+        let innertube = innertube();
+        let channel = innertube.channel_info("@RickAstleyYT").await.unwrap();
+        assert_eq!(channel.channel_id, "UCuAXFkgsw1L7xaCfnd5JJOw");
+        assert_eq!(channel.title, "Rick Astley");
+
+        let res = innertube
+            .channel_info("https://example.com/@RickAstleyYT")
+            .await;
+        assert!(matches!(res, Err(Error::NotYoutubeUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_info_invalid_video() {
+        // the token marks the video as invalid, every stream would respond with a 403
+        let player = PLAYER_JSON.replace("23804281,23946420", "23804281,51217102");
+        let innertube = innertube_with(
+            ClientType::Ios,
+            MockTransport {
+                player,
+                ..MockTransport::new()
+            },
+        );
+        let res = innertube.info("dQw4w9WgXcQ").await;
+        let Err(Error::AllClientsFailed { id, failures }) = res else {
+            panic!("expected all clients to fail");
+        };
+        assert_eq!(id, "dQw4w9WgXcQ");
+        assert_eq!(
+            failures,
+            [ClientFailure {
+                client_name: "IOS",
+                cause: ClientFailureCause::InvalidResponse,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_info_http_error() {
+        let http = MockTransport {
+            forbidden: Some("IOS"),
+            ..MockTransport::new()
+        };
+        let innertube = Innertube::new(Config {
+            configs: vec![
+                ClientConfig::new(ClientType::Ios),
+                ClientConfig::new(ClientType::Android),
+            ],
+            retry_limit: 0,
+            ..Config::with_client(http)
+        })
+        .unwrap();
+        // the error body is not taken as a player response, the next client is tried instead
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+
+        let http = MockTransport {
+            forbidden: Some("IOS"),
+            ..MockTransport::new()
+        };
+        let res = innertube_with(ClientType::Ios, http)
+            .info("dQw4w9WgXcQ")
+            .await;
+        let Err(Error::AllClientsFailed { failures, .. }) = res else {
+            panic!("expected all clients to fail");
+        };
+        assert_eq!(
+            failures,
+            [ClientFailure {
+                client_name: "IOS",
+                cause: ClientFailureCause::HttpStatus(403),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_info_unplayable() {
+        let player = PLAYER_JSON.replacen(
+            r#""status": "OK","#,
+            r#""status": "LOGIN_REQUIRED", "reason": "This video is private","#,
+            1,
+        );
+        let innertube = innertube_with(
+            ClientType::Ios,
+            MockTransport {
+                player,
+                ..MockTransport::new()
+            },
+        );
+        let res = innertube.info("dQw4w9WgXcQ").await;
+        let Err(Error::Unplayable { id, status, reason }) = res else {
+            panic!("expected the video to be unplayable");
+        };
+        assert_eq!(id, "dQw4w9WgXcQ");
+        assert_eq!(status, PlayabilityState::LoginRequired);
+        assert_eq!(reason.as_deref(), Some("This video is private"));
+    }
+
+    #[tokio::test]
+    async fn test_info_age_restricted_fallback() {
+        let player = PLAYER_JSON.replacen(
+            r#""status": "OK","#,
+            r#""status": "LOGIN_REQUIRED", "reason": "Sign in to confirm your age","#,
+            1,
+        );
+        let http = MockTransport {
+            player: player.clone(),
+            tv_player: Some(PLAYER_JSON.to_owned()),
+            ..MockTransport::new()
+        };
+        let innertube = innertube_with(ClientType::Ios, http);
+        let res = innertube.info("dQw4w9WgXcQ").await;
+        assert!(matches!(res, Err(Error::AgeRestricted { .. })));
+
+        let http = MockTransport {
+            player,
+            tv_player: Some(PLAYER_JSON.to_owned()),
+            ..MockTransport::new()
+        };
+        let innertube = Innertube::new(Config {
+            configs: vec![ClientConfig::new(ClientType::Ios)],
+            retry_limit: 0,
+            age_restricted_fallback: true,
+            ..Config::with_client(http)
+        })
+        .unwrap();
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(video.video_details.video_id, "dQw4w9WgXcQ");
+    }
+
+    #[tokio::test]
+    async fn test_info_geo_restricted() {
+        let player = PLAYER_JSON.replacen(
+            r#""status": "OK","#,
+            r#""status": "UNPLAYABLE", "reason": "Video unavailable in your country","#,
+            1,
+        );
+        let innertube = innertube_with(
+            ClientType::Ios,
+            MockTransport {
+                player,
+                ..MockTransport::new()
+            },
+        );
+        let res = innertube.info("dQw4w9WgXcQ").await;
+        let Err(Error::GeoRestricted { id, reason }) = res else {
+            panic!("expected the video to be geo restricted");
+        };
+        assert_eq!(id, "dQw4w9WgXcQ");
+        assert_eq!(reason, "Video unavailable in your country");
+    }
+
+    #[tokio::test]
+    async fn test_info_upcoming() {
+        let player = PLAYER_JSON.replacen(
+            r#""status": "OK","#,
+            r#""status": "LIVE_STREAM_OFFLINE", "liveStreamability": {"liveStreamabilityRenderer": {"offlineSlate": {"liveStreamOfflineSlateRenderer": {"scheduledStartTime": "1720026000"}}}},"#,
+            1,
+        );
+        let innertube = innertube_with(
+            ClientType::Ios,
+            MockTransport {
+                player,
+                ..MockTransport::new()
+            },
+        );
+        let res = innertube.info("dQw4w9WgXcQ").await;
+        let Err(Error::NotYetAvailable {
+            scheduled_start, ..
+        }) = res
+        else {
+            panic!("expected the video to be upcoming");
+        };
+        assert_eq!(
+            scheduled_start,
+            UNIX_EPOCH + Duration::from_secs(1_720_026_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decipher_format() {
+        let innertube = innertube();
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter()
+            .find(|x| x.itag == 248)
+            .unwrap();
+
+        let url = innertube.decipher_format(format).await.unwrap();
+        assert!(url
+            .as_str()
+            .starts_with("https://rr3---sn-a5mekn6r.googlevideo.com/videoplayback?"));
+        assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+        assert_eq!(url.param("sig").map(|x| x.len()), Some(103));
+
+        // the context is reused, so deciphering again goes through the same one
+        let again = innertube.decipher_format(format).await.unwrap();
+        assert_eq!(again.param("n"), url.param("n"));
+        assert_eq!(again.param("sig"), url.param("sig"));
+        assert!(innertube.js_context.initialized());
+    }
+
+    #[tokio::test]
+    async fn test_decipher_retries() {
+        let http = MockTransport {
+            flaky: true,
+            ..MockTransport::new()
+        };
+        let innertube = Innertube::new(Config {
+            configs: vec![ClientConfig::new(ClientType::Ios)],
+            retry_limit: 1,
+            ..Config::with_client(http)
+        })
+        .unwrap();
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        let format = video.format_by_itag(248).unwrap();
+        let url = innertube.decipher_format(format).await.unwrap();
+        assert_eq!(url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+    }
+
+    #[tokio::test]
+    async fn test_decipher_failure_not_cached() {
+        let innertube = innertube_with(
+            ClientType::Ios,
+            MockTransport {
+                flaky: true,
+                ..MockTransport::new()
+            },
+        );
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        let format = video.format_by_itag(248).unwrap();
+
+        // the embed page and the player js each fail once without retries
+        for _ in 0..2 {
+            let res = innertube.decipher_format(format).await;
+            assert!(matches!(res, Err(Error::HttpStatus(500, _))));
+        }
+        assert!(innertube.cipher_cache.is_empty());
+        assert!(innertube.decipher_format(format).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_decipher_to_stream() {
+        let innertube = innertube();
+        let video = innertube.info("dQw4w9WgXcQ").await.unwrap();
+        let mut format = video.format_by_itag(248).unwrap().clone();
+        // the url is preferred over the format
+        format.content_length = Some("1".to_owned());
+        format.approx_duration_ms = None;
+
+        let stream = innertube.decipher_to_stream(&format).await.unwrap();
+        assert_eq!(stream.itag, 248);
+        assert_eq!(stream.mime, format.mime_type);
+        assert_eq!(stream.content_length, Some(63_115_478));
+        assert_eq!(stream.duration, Some(Duration::from_millis(253_040)));
+        assert_eq!(
+            stream.expires_at,
+            Some(UNIX_EPOCH + Duration::from_secs(1_720_021_540))
+        );
+        assert_eq!(stream.url.param("n").as_deref(), Some("NmLkJiHgFeDcBa"));
+    }
+
+    #[tokio::test]
+    async fn test_search_suggestions_transport() {
+        let suggestions = innertube().search_suggestions("rust").await.unwrap();
+        assert_eq!(suggestions, ["rust", "rust game"]);
+
+        // the mock only answers the default locale, other locales get a 404
+        let innertube = Innertube::builder()
+            .client(ClientType::Ios)
+            .http(MockTransport::new())
+            .hl("de")
+            .build()
+            .unwrap();
+        let res = innertube.search_suggestions("rust").await;
+        assert!(matches!(res, Err(Error::HttpStatus(404, _))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_status() {
+        let res = innertube()
+            .fetch_dash_manifest("https://example.com/manifest.mpd")
+            .await;
+        assert!(matches!(res, Err(Error::HttpStatus(404, _))));
+    }
+
+    #[tokio::test]
+    async fn test_post_error_status() {
+        let res = innertube().search("rust").await;
+        assert!(matches!(res, Err(Error::HttpStatus(404, _))));
+
+        // a json error body must not be taken as the response
+        let innertube = innertube();
+        let res = innertube
+            .post::<Value>("error", &innertube.web_config, &json!({}))
+            .await;
+        assert!(
+            matches!(res, Err(Error::HttpStatus(403, body)) if body.contains("PERMISSION_DENIED"))
+        );
+    }
 
     #[test]
     fn test_get_video_id() {
@@ -382,4 +1945,32 @@ mod tests {
             assert_eq!(get_video_id(str), None);
         }
     }
+
+    #[test]
+    fn test_get_playlist_id() {
+        let strings = [
+            "https://www.youtube.com/playlist?list=PLBCF2DAC6FFB574DE",
+            "https://www.youtube.com/watch?v=NLqAF9hrVbY&list=PLBCF2DAC6FFB574DE&index=2",
+            "https://music.youtube.com/playlist?list=PLBCF2DAC6FFB574DE",
+            "PLBCF2DAC6FFB574DE",
+        ];
+        for str in strings {
+            assert_eq!(get_playlist_id(str), Some("PLBCF2DAC6FFB574DE"));
+        }
+        assert_eq!(get_playlist_id("RDdQw4w9WgXcQ"), Some("RDdQw4w9WgXcQ"));
+    }
+
+    #[test]
+    fn test_get_playlist_id_bad() {
+        let strings = [
+            "NLqAF9hrVbY",
+            "PL",
+            "PLBCF2DA!C6FFB574DE",
+            "https://example.com/playlist?list=PLBCF2DAC6FFB574DE",
+            "https://www.youtube.com/watch?v=NLqAF9hrVbY",
+        ];
+        for str in strings {
+            assert_eq!(get_playlist_id(str), None);
+        }
+    }
 }
@@ -14,7 +14,8 @@
 //! For example, to fetch information about a video
 //!
 //! ```no_run
-//! # use yinfo::{Innertube, Config};
+//! # use yinfo::{Innertube, Config, Error};
+//! # #[cfg(feature = "reqwest")]
 //! # async fn run() -> Result<(), Error> {
 //! let innertube = Innertube::new(Config::default())?;
 //! let video_info = innertube.info("https://www.youtube.com/watch?v=5C_HPTJg5ek").await?;
@@ -26,6 +27,7 @@
 //!
 //! ```no_run
 //! # use yinfo::{Innertube, Config, Error};
+//! # #[cfg(feature = "reqwest")]
 //! # async fn run() -> Result<(), Error> {
 //! let innertube = Innertube::new(Config::default())?;
 //! let results = innertube.search("how to learn rust").await?;
@@ -43,12 +45,14 @@
 //! on the desired format, as formats are not automatically deciphered.
 //!
 //! ```no_run
-//! # use yinfo::{Innertube, Config};
+//! # use yinfo::{Innertube, Config, Error};
+//! # #[cfg(feature = "reqwest")]
 //! # async fn run() -> Result<(), Error> {
 //! let innertube = Innertube::new(Config::default())?;
 //! let video_info = innertube.info("https://www.youtube.com/watch?v=5C_HPTJg5ek").await?;
-//! let format = video_info.best_audio()?;
-//! let url = innertube.decipher_format(&format)?;
+//! if let Some(format) = video_info.best_audio() {
+//!     let url = innertube.decipher_format(format).await?;
+//! }
 //! # Ok(())
 //! # }
 //! ```
@@ -60,15 +64,16 @@
 //!
 //! ```no_run
 //! # use yinfo::{Innertube, Config, ClientConfig, ClientType, Error};
+//! # #[cfg(feature = "reqwest")]
 //! # fn run() -> Result<(), Error> {
 //! let config = Config {
-//!     http: reqwest::Client::new(),
 //!     retry_limit: 1,
 //!     configs: vec![
 //!         ClientConfig::new(ClientType::Web),
 //!         ClientConfig::new(ClientType::Ios),
 //!         ClientConfig::new(ClientType::Android),
 //!     ],
+//!     ..Config::with_client(reqwest::Client::new())
 //! };
 //! # Ok(())
 //! # }
@@ -78,19 +83,47 @@
 //! of potentially more requests and maybe even being ratelimited.
 //!
 //! Not all clients are the same, see [`ClientType`] for more information.
+//!
+//! Each `reqwest::Client` keeps its own connection pool, so clone one client rather than
+//! creating a new one when using several [`Innertube`] instances, see [`Config::with_client()`].
+//! The pool of the default client is configured with `Config::with_pool()` or the pool options
+//! of [`InnertubeBuilder`].
+//!
+//! ## Features
+//!
+//! * `reqwest` (default): implements [`HttpTransport`] for `reqwest::Client`.
+//! * `transcript` (default): fetching and parsing caption transcripts.
+//! * `tracing`: spans for video requests, searches and deciphering, recording the video id,
+//!   client and retry attempt.
+pub mod channel;
 pub mod cipher;
 pub mod clients;
 pub mod errors;
 pub mod innertube;
 pub mod mime;
 mod query;
+pub mod select;
+pub mod storyboard;
 pub mod structs;
+#[cfg(feature = "transcript")]
+pub mod transcript;
+pub mod transport;
 mod utils;
 
 pub use {
+    channel::ChannelInfo,
+    cipher::{DecipheredStream, DecipheredUrl},
     clients::{ClientConfig, ClientType},
-    errors::Error,
-    innertube::{Config, Innertube},
-    mime::{Acodec, Format, Mime, Vcodec},
-    structs::{Video, VideoFormat},
+    errors::{ClientFailure, ClientFailureCause, Error},
+    innertube::{Config, Innertube, InnertubeBuilder},
+    mime::{Acodec, Av1Profile, AvcProfile, Format, Mime, Vcodec},
+    select::{FormatPreferences, FormatSelector},
+    storyboard::StoryboardLevel,
+    structs::{
+        compare_audio_default, compare_quality, compare_video_default, AudioTrack, CaptionTrack,
+        ChannelSearchResult, ChapterInfo, ContinuationToken, Date, HeatMarker, LiveStatus,
+        PlayabilityState, PlaylistInfo, PlaylistSearchResult, PlaylistVideo, ProjectionType,
+        Quality, QualityLabel, RelatedVideo, Video, VideoFormat, VideoId,
+    },
+    transport::HttpTransport,
 };
@@ -1,6 +1,9 @@
 use std::{fmt, str::FromStr};
 
-use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    Serialize, Serializer,
+};
 
 use crate::{errors::Error, utils::between};
 
@@ -10,10 +13,22 @@ use crate::{errors::Error, utils::between};
 /// For each type, codecs are given for both audio and video if they exist, along with the
 /// format/container of the stream.
 ///
-/// The codecs and format implement ord so you can compare them to see which one is better.
-/// For [`Format`], it is best to keep it to audio-audio or video-video comparisons and not
-/// audio-video, which gets tricky, depending if you prefer audio or video formats more.
-#[derive(Debug, serde::Serialize)]
+/// The codecs and format implement ord, which gives them a stable order for sorting and is the
+/// default preference when picking formats. Which codec is better depends on the player, use
+/// [`crate::select::FormatPreferences`] or [`crate::select::FormatSelector`] to rank them
+/// differently. For [`Format`], it is best to keep it to audio-audio or video-video comparisons
+/// and not audio-video, which gets tricky, depending if you prefer audio or video formats more.
+///
+/// Mimes are displayed and serialized as mime strings such as `audio/webm; codecs="opus"`.
+/// Codec profiles are not part of the parsed codecs, so `avc1.4d401f` is written as `avc1`.
+/// [`crate::structs::VideoFormat`] keeps the mime string it was parsed from alongside its mime
+/// and writes that back instead, so formats serialize losslessly. The full codec strings are
+/// available through [`crate::structs::VideoFormat::vcodec_raw()`] and
+/// [`crate::structs::VideoFormat::acodec_raw()`].
+///
+/// Codecs not known to this crate are parsed as [`Vcodec::Unknown`] or [`Acodec::Unknown`]
+/// rather than failing, so a single new codec does not make a whole video fail to deserialize.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Mime {
     Audio(Format, Acodec),
     Video(Format, Vcodec, Option<Acodec>),
@@ -45,6 +60,53 @@ impl Mime {
             Mime::Audio(format, _) | Mime::Video(format, _, _) => *format,
         }
     }
+
+    /// Returns whether the mime is audio only.
+    #[must_use]
+    pub fn is_audio(&self) -> bool {
+        matches!(self, Mime::Audio(..))
+    }
+
+    /// Returns whether the mime is video, with or without audio.
+    #[must_use]
+    pub fn is_video(&self) -> bool {
+        matches!(self, Mime::Video(..))
+    }
+
+    /// Returns whether the mime contains audio, either audio only or video with audio.
+    #[must_use]
+    pub fn has_audio(&self) -> bool {
+        self.acodec().is_some()
+    }
+
+    /// Returns whether the video or audio codec is not known to this crate.
+    #[must_use]
+    pub fn has_unknown_codec(&self) -> bool {
+        self.vcodec() == Some(Vcodec::Unknown) || self.acodec() == Some(Acodec::Unknown)
+    }
+
+    /// Returns the type of the mime, `"audio"` or `"video"`.
+    #[must_use]
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Mime::Audio(..) => "audio",
+            Mime::Video(..) => "video",
+        }
+    }
+}
+
+impl fmt::Display for Mime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mime::Audio(format, acodec) => write!(f, "audio/{format}; codecs=\"{acodec}\""),
+            Mime::Video(format, vcodec, None) => {
+                write!(f, "video/{format}; codecs=\"{vcodec}\"")
+            }
+            Mime::Video(format, vcodec, Some(acodec)) => {
+                write!(f, "video/{format}; codecs=\"{vcodec}, {acodec}\"")
+            }
+        }
+    }
 }
 
 impl FromStr for Mime {
@@ -53,22 +115,25 @@ impl FromStr for Mime {
     fn from_str(input: &str) -> Result<Mime, Self::Err> {
         let input = input.trim();
 
-        let format = between(input, "/", ";").parse::<Format>()?;
-        let codecs = between(input, "\"", "\"");
+        let format = between(input, "/", ";")
+            .ok_or_else(|| Error::MimeParse(";", input.to_owned()))?
+            .parse::<Format>()?;
+        let codecs = between(input, "\"", "\"")
+            .ok_or_else(|| Error::MimeParse("codecs", input.to_owned()))?;
         let split = input
             .find('/')
             .ok_or(Error::MimeParse("/", String::new()))?;
 
         match &input[..split] {
-            "audio" => Ok(Mime::Audio(format, codecs.parse::<Acodec>()?)),
+            "audio" => Ok(Mime::Audio(format, Acodec::parse_lenient(codecs))),
             "video" => {
                 let acodec_start = codecs.find(',');
                 if let Some(pos) = acodec_start {
-                    let vcodec = codecs[..pos].parse::<Vcodec>()?;
-                    let acodec = codecs[(pos + 1)..].trim().parse::<Acodec>()?;
+                    let vcodec = Vcodec::parse_lenient(&codecs[..pos]);
+                    let acodec = Acodec::parse_lenient(codecs[(pos + 1)..].trim());
                     Ok(Mime::Video(format, vcodec, Some(acodec)))
                 } else {
-                    Ok(Mime::Video(format, codecs.parse::<Vcodec>()?, None))
+                    Ok(Mime::Video(format, Vcodec::parse_lenient(codecs), None))
                 }
             }
             _ => Err(Error::MimeParse(
@@ -79,6 +144,88 @@ impl FromStr for Mime {
     }
 }
 
+/// Profile and level of an AVC (H.264) codec, parsed from codec strings such as `avc1.64001F`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AvcProfile {
+    /// The `profile_idc`, such as 66 for Baseline, 77 for Main or 100 for High.
+    pub profile: u8,
+    /// The constraint flags, for example Constrained Baseline is Baseline with `0x40` set.
+    pub constraints: u8,
+    /// The `level_idc`, which is ten times the level, such as 31 for level 3.1.
+    pub level: u8,
+}
+
+impl AvcProfile {
+    /// Parses an `avc1` or `avc3` codec string, which holds the profile, constraints and level
+    /// as hex bytes. Returns `None` for other codecs or codec strings without a profile.
+    #[must_use]
+    pub fn parse(codec: &str) -> Option<Self> {
+        let hex = codec
+            .strip_prefix("avc1.")
+            .or_else(|| codec.strip_prefix("avc3."))?;
+        if hex.len() != 6 || !hex.is_char_boundary(2) || !hex.is_char_boundary(4) {
+            return None;
+        }
+        let byte = |range| u8::from_str_radix(&hex[range], 16).ok();
+        Some(AvcProfile {
+            profile: byte(0..2)?,
+            constraints: byte(2..4)?,
+            level: byte(4..6)?,
+        })
+    }
+
+    /// Returns the name of the profile, such as `"High"`, if it is a common one.
+    #[must_use]
+    pub fn profile_name(&self) -> Option<&'static str> {
+        Some(match self.profile {
+            66 if self.constraints & 0x40 != 0 => "Constrained Baseline",
+            66 => "Baseline",
+            77 => "Main",
+            88 => "Extended",
+            100 => "High",
+            110 => "High 10",
+            122 => "High 4:2:2",
+            244 => "High 4:4:4",
+            _ => return None,
+        })
+    }
+}
+
+/// Profile, level and tier of an AV1 codec, parsed from codec strings such as `av01.0.08M.08`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Av1Profile {
+    /// The `seq_profile`, 0 for Main, 1 for High and 2 for Professional.
+    pub profile: u8,
+    /// The `seq_level_idx`, such as 8 for level 4.0.
+    pub level: u8,
+    /// Whether the high tier is used, which allows higher bitrates than the main tier.
+    pub high_tier: bool,
+    pub bit_depth: u8,
+}
+
+impl Av1Profile {
+    /// Parses an `av01` codec string of the form `av01.P.LLT.DD`, trailing fields are ignored.
+    /// Returns `None` for other codecs or codec strings without a profile.
+    #[must_use]
+    pub fn parse(codec: &str) -> Option<Self> {
+        let mut parts = codec.strip_prefix("av01.")?.split('.');
+        let profile = parts.next()?.parse().ok()?;
+        let level_tier = parts.next()?;
+        let (level, tier) = level_tier.split_at_checked(level_tier.len().checked_sub(1)?)?;
+        let high_tier = match tier {
+            "M" => false,
+            "H" => true,
+            _ => return None,
+        };
+        Some(Av1Profile {
+            profile,
+            level: level.parse().ok()?,
+            high_tier,
+            bit_depth: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
 struct MimeVisitor;
 
 impl<'de> Visitor<'de> for MimeVisitor {
@@ -96,6 +243,15 @@ impl<'de> Visitor<'de> for MimeVisitor {
     }
 }
 
+impl Serialize for Mime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<'de> Deserialize<'de> for Mime {
     fn deserialize<D>(deserializer: D) -> Result<Mime, D::Error>
     where
@@ -106,12 +262,37 @@ impl<'de> Deserialize<'de> for Mime {
 }
 
 /// Format/container
+///
+/// Ordered as 3GP, HLS, Webm and MP4, which is also the default preference. 3GP is only used by
+/// legacy formats and HLS by formats derived from HLS manifests, so both are ranked below the
+/// regular containers.
+///
+/// ```
+/// # use yinfo::{Error, Format};
+/// let format: Format = "webm".try_into()?;
+/// assert_eq!(format, Format::Webm);
+/// # Ok::<(), Error>(())
+/// ```
 #[derive(Debug, Clone, Copy, serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Format {
+    ThreeGP,
+    /// MPEG transport streams of HLS, given as either `mp2t` or `vnd.apple.mpegurl`.
+    HLS,
     Webm,
     MP4,
 }
 
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Webm => "webm",
+            Format::MP4 => "mp4",
+            Format::ThreeGP => "3gpp",
+            Format::HLS => "mp2t",
+        })
+    }
+}
+
 impl FromStr for Format {
     type Err = Error;
 
@@ -120,45 +301,165 @@ impl FromStr for Format {
             Ok(Format::MP4)
         } else if input.starts_with("webm") {
             Ok(Format::Webm)
+        } else if input.starts_with("3gpp") {
+            Ok(Format::ThreeGP)
+        } else if input.starts_with("mp2t") || input.starts_with("vnd.apple.mpegurl") {
+            Ok(Format::HLS)
         } else {
             Err(Error::MimeParse("format", input.to_owned()))
         }
     }
 }
 
+impl TryFrom<&str> for Format {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Format, Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for Format {
+    type Error = Error;
+
+    fn try_from(input: String) -> Result<Format, Error> {
+        input.parse()
+    }
+}
+
 /// Video codec
+///
+/// Ordered as unknown, MPEG-4 Visual, VP8, AVC, HEVC, AV1 and VP9. This is the default
+/// preference of [`crate::structs::Video::best_video()`], use
+/// [`crate::select::FormatPreferences::vcodec_order()`] to prefer codecs supported by your
+/// player instead.
+///
+/// ```
+/// # use yinfo::{Error, Vcodec};
+/// let vcodec: Vcodec = "av01".try_into()?;
+/// assert_eq!(vcodec, Vcodec::AV1);
+/// # Ok::<(), Error>(())
+/// ```
 #[derive(Debug, Clone, Copy, serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Vcodec {
+    /// A codec not known to this crate, only produced when parsing a [`Mime`].
+    Unknown,
+    /// MPEG-4 Part 2, only used by legacy 3GP formats.
+    MP4V,
+    VP8,
     AVC,
+    HEVC,
     AV1,
     // prefer VP9 for now over AV1 due to lack of support for AV1
     VP9,
 }
 
+impl fmt::Display for Vcodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Vcodec::AVC => "avc1",
+            Vcodec::AV1 => "av01",
+            Vcodec::VP9 => "vp9",
+            Vcodec::VP8 => "vp8",
+            Vcodec::HEVC => "hev1",
+            Vcodec::MP4V => "mp4v",
+            Vcodec::Unknown => "unknown",
+        })
+    }
+}
+
+impl Vcodec {
+    fn parse_lenient(input: &str) -> Vcodec {
+        input.parse().unwrap_or(Vcodec::Unknown)
+    }
+}
+
 impl FromStr for Vcodec {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Vcodec, Error> {
         if input.starts_with("av01") {
             Ok(Vcodec::AV1)
-        } else if input.starts_with("vp9") {
+        } else if input.starts_with("vp9") || input.starts_with("vp09") {
             Ok(Vcodec::VP9)
         } else if input.starts_with("avc1") {
             Ok(Vcodec::AVC)
+        } else if input.starts_with("vp8") || input.starts_with("vp08") {
+            Ok(Vcodec::VP8)
+        } else if input.starts_with("hev1") || input.starts_with("hvc1") {
+            Ok(Vcodec::HEVC)
+        } else if input.starts_with("mp4v") {
+            Ok(Vcodec::MP4V)
         } else {
             Err(Error::MimeParse("video codec", input.to_owned()))
         }
     }
 }
 
+impl TryFrom<&str> for Vcodec {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Vcodec, Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for Vcodec {
+    type Error = Error;
+
+    fn try_from(input: String) -> Result<Vcodec, Error> {
+        input.parse()
+    }
+}
+
 /// Audio codec
+///
+/// Ordered as unknown, DTS Express, AC-3, E-AC-3, MP4A, AAC, Vorbis, Opus and FLAC. This is the
+/// default preference of [`crate::structs::Video::best_audio()`], where the surround codecs are
+/// ranked low as few players outside of TVs support them. Use
+/// [`crate::select::FormatPreferences::acodec_order()`] to prefer other codecs, such as AAC for
+/// Apple devices.
+///
+/// ```
+/// # use yinfo::{Acodec, Error};
+/// let acodec: Acodec = "mp4a.40.2".try_into()?;
+/// assert_eq!(acodec, Acodec::AAC);
+/// # Ok::<(), Error>(())
+/// ```
 #[derive(Debug, Clone, Copy, serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Acodec {
+    /// A codec not known to this crate, only produced when parsing a [`Mime`].
+    Unknown,
+    DTSE,
+    AC3,
+    EC3,
     MP4A,
     AAC,
     Vorbis,
     Opus,
-    // FLAC,
+    FLAC,
+}
+
+impl fmt::Display for Acodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Acodec::MP4A => "mp4a",
+            Acodec::AAC => "mp4a.40.2",
+            Acodec::Vorbis => "vorbis",
+            Acodec::Opus => "opus",
+            Acodec::FLAC => "flac",
+            Acodec::AC3 => "ac-3",
+            Acodec::EC3 => "ec-3",
+            Acodec::DTSE => "dtse",
+            Acodec::Unknown => "unknown",
+        })
+    }
+}
+
+impl Acodec {
+    fn parse_lenient(input: &str) -> Acodec {
+        input.parse().unwrap_or(Acodec::Unknown)
+    }
 }
 
 impl FromStr for Acodec {
@@ -174,8 +475,248 @@ impl FromStr for Acodec {
             Ok(Acodec::AAC)
         } else if input.starts_with("mp4a") {
             Ok(Acodec::MP4A)
+        } else if input.starts_with("flac") || input.starts_with("fLaC") {
+            Ok(Acodec::FLAC)
+        } else if input.starts_with("ac-3") {
+            Ok(Acodec::AC3)
+        } else if input.starts_with("ec-3") {
+            Ok(Acodec::EC3)
+        } else if input.starts_with("dtse") {
+            Ok(Acodec::DTSE)
         } else {
             Err(Error::MimeParse("audio codec", input.to_owned()))
         }
     }
 }
+
+impl TryFrom<&str> for Acodec {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Acodec, Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for Acodec {
+    type Error = Error;
+
+    fn try_from(input: String) -> Result<Acodec, Error> {
+        input.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        for mime in [
+            r#"audio/webm; codecs="opus""#,
+            r#"audio/mp4; codecs="mp4a.40.2""#,
+            r#"video/webm; codecs="vp9""#,
+            r#"video/mp4; codecs="av01, mp4a""#,
+        ] {
+            assert_eq!(mime.parse::<Mime>().unwrap().to_string(), mime);
+        }
+
+        let mime: Mime = r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#.parse().unwrap();
+        assert_eq!(mime.to_string(), r#"video/mp4; codecs="avc1, mp4a.40.2""#);
+        assert_eq!(mime.to_string().parse::<Mime>().unwrap(), mime);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let audio = Mime::Audio(Format::Webm, Acodec::Opus);
+        let video = Mime::Video(Format::MP4, Vcodec::AVC, None);
+        let muxed = Mime::Video(Format::MP4, Vcodec::AVC, Some(Acodec::AAC));
+
+        assert!(audio.is_audio() && !audio.is_video() && audio.has_audio());
+        assert!(!video.is_audio() && video.is_video() && !video.has_audio());
+        assert!(!muxed.is_audio() && muxed.is_video() && muxed.has_audio());
+        for mime in [audio, video, muxed] {
+            assert!(mime.to_string().starts_with(mime.content_type()));
+        }
+    }
+
+    #[test]
+    fn test_unknown_codecs() {
+        let mime: Mime = r#"video/mp4; codecs="dvh1.05.06, ec-4""#.parse().unwrap();
+        assert_eq!(
+            mime,
+            Mime::Video(Format::MP4, Vcodec::Unknown, Some(Acodec::Unknown))
+        );
+        assert!(mime.has_unknown_codec());
+        let mime: Mime = r#"audio/mp4; codecs="iamf""#.parse().unwrap();
+        assert_eq!(mime, Mime::Audio(Format::MP4, Acodec::Unknown));
+        assert_eq!(mime.to_string().parse::<Mime>().unwrap(), mime);
+        assert!(!Mime::Audio(Format::MP4, Acodec::AAC).has_unknown_codec());
+
+        // only mimes are lenient
+        assert!("iamf".parse::<Acodec>().is_err());
+        assert!(Vcodec::Unknown < Vcodec::MP4V);
+        assert!(Acodec::Unknown < Acodec::DTSE);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        for mime in [
+            r#"audio/webm; codecs="opus""#,
+            r#"video/mp4; codecs="avc1.64001F""#,
+            r#"video/mp4; codecs="avc1.64001F, mp4a.40.2""#,
+        ] {
+            let mime: Mime = serde_json::from_value(mime.into()).unwrap();
+            let json = serde_json::to_value(&mime).unwrap();
+            assert!(json.is_string());
+            assert_eq!(serde_json::from_value::<Mime>(json).unwrap(), mime);
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        let profile = AvcProfile::parse("avc1.64001F").unwrap();
+        assert_eq!((profile.profile, profile.level), (100, 31));
+        assert_eq!(profile.profile_name(), Some("High"));
+        let profile = AvcProfile::parse("avc1.42001E").unwrap();
+        assert_eq!((profile.profile, profile.level), (66, 30));
+        assert_eq!(profile.profile_name(), Some("Baseline"));
+        assert_eq!(
+            AvcProfile::parse("avc1.42E01E").unwrap().profile_name(),
+            Some("Constrained Baseline")
+        );
+        assert_eq!(AvcProfile::parse("avc1"), None);
+        assert_eq!(AvcProfile::parse("avc1.64zz1F"), None);
+        assert_eq!(AvcProfile::parse("av01.0.08M.08"), None);
+
+        assert_eq!(
+            Av1Profile::parse("av01.0.08M.08"),
+            Some(Av1Profile {
+                profile: 0,
+                level: 8,
+                high_tier: false,
+                bit_depth: 8
+            })
+        );
+        let profile = Av1Profile::parse("av01.2.12H.10.0.110.09.16.09.0").unwrap();
+        assert!(profile.high_tier);
+        assert_eq!(
+            (profile.profile, profile.level, profile.bit_depth),
+            (2, 12, 10)
+        );
+        assert_eq!(Av1Profile::parse("av01"), None);
+        assert_eq!(Av1Profile::parse("av01.0.08X.08"), None);
+        assert_eq!(Av1Profile::parse("avc1.64001F"), None);
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Format::try_from("mp4").unwrap(), Format::MP4);
+        assert_eq!(Vcodec::try_from("vp9").unwrap(), Vcodec::VP9);
+        assert_eq!(Acodec::try_from("opus").unwrap(), Acodec::Opus);
+        assert_eq!(Format::try_from("webm".to_owned()).unwrap(), Format::Webm);
+        assert_eq!(Vcodec::try_from("avc1".to_owned()).unwrap(), Vcodec::AVC);
+        assert_eq!(
+            Acodec::try_from("vorbis".to_owned()).unwrap(),
+            Acodec::Vorbis
+        );
+        assert!(matches!(
+            Vcodec::try_from("theora"),
+            Err(Error::MimeParse("video codec", _))
+        ));
+    }
+
+    #[test]
+    fn test_format() {
+        for (format, expected) in [
+            ("3gpp", Format::ThreeGP),
+            ("mp2t", Format::HLS),
+            ("vnd.apple.mpegurl", Format::HLS),
+            ("webm", Format::Webm),
+        ] {
+            assert_eq!(format.parse::<Format>().unwrap(), expected);
+        }
+        assert!("ogg".parse::<Format>().is_err());
+
+        let mime: Mime = r#"video/3gpp; codecs="mp4v.20.3, mp4a.40.2""#.parse().unwrap();
+        assert_eq!(
+            mime,
+            Mime::Video(Format::ThreeGP, Vcodec::MP4V, Some(Acodec::AAC))
+        );
+        assert_eq!(mime.to_string(), r#"video/3gpp; codecs="mp4v, mp4a.40.2""#);
+        let mime: Mime = r#"video/mp2t; codecs="avc1.4D401E, mp4a.40.2""#.parse().unwrap();
+        assert_eq!(mime.format(), Format::HLS);
+
+        assert!(Format::ThreeGP < Format::Webm);
+        assert!(Format::HLS < Format::Webm);
+        assert!(Vcodec::MP4V < Vcodec::VP8);
+    }
+
+    #[test]
+    fn test_vcodec() {
+        for (codec, vcodec) in [
+            ("vp8", Vcodec::VP8),
+            ("vp08.00.10.08", Vcodec::VP8),
+            ("hev1.1.6.L93.B0", Vcodec::HEVC),
+            ("hvc1.2.4.L120.90", Vcodec::HEVC),
+            ("vp09.00.51.08", Vcodec::VP9),
+            ("avc1.4d401f", Vcodec::AVC),
+        ] {
+            assert_eq!(codec.parse::<Vcodec>().unwrap(), vcodec);
+        }
+        assert!("theora".parse::<Vcodec>().is_err());
+
+        let mime: Mime = r#"video/webm; codecs="vp8, vorbis""#.parse().unwrap();
+        assert_eq!(
+            mime,
+            Mime::Video(Format::Webm, Vcodec::VP8, Some(Acodec::Vorbis))
+        );
+        let mime: Mime = r#"video/mp4; codecs="hvc1.2.4.L120.90""#.parse().unwrap();
+        assert_eq!(mime.to_string(), r#"video/mp4; codecs="hev1""#);
+
+        assert!(Vcodec::VP8 < Vcodec::AVC);
+        assert!(Vcodec::AVC < Vcodec::HEVC);
+        assert!(Vcodec::HEVC < Vcodec::AV1);
+    }
+
+    #[test]
+    fn test_acodec() {
+        for (codec, acodec) in [
+            ("ac-3", Acodec::AC3),
+            ("ec-3", Acodec::EC3),
+            ("dtse", Acodec::DTSE),
+            ("flac", Acodec::FLAC),
+            ("fLaC", Acodec::FLAC),
+            ("mp4a.40.5", Acodec::MP4A),
+        ] {
+            assert_eq!(codec.parse::<Acodec>().unwrap(), acodec);
+        }
+        assert!("alac".parse::<Acodec>().is_err());
+
+        let mime: Mime = r#"audio/mp4; codecs="ec-3""#.parse().unwrap();
+        assert_eq!(mime, Mime::Audio(Format::MP4, Acodec::EC3));
+        assert_eq!(mime.to_string(), r#"audio/mp4; codecs="ec-3""#);
+        let mime: Mime = r#"video/mp4; codecs="avc1.64001F, ac-3""#.parse().unwrap();
+        assert_eq!(mime.acodec(), Some(Acodec::AC3));
+
+        let mut codecs = [
+            Acodec::FLAC,
+            Acodec::Opus,
+            Acodec::AAC,
+            Acodec::EC3,
+            Acodec::DTSE,
+            Acodec::AC3,
+        ];
+        codecs.sort();
+        assert_eq!(
+            codecs,
+            [
+                Acodec::DTSE,
+                Acodec::AC3,
+                Acodec::EC3,
+                Acodec::AAC,
+                Acodec::Opus,
+                Acodec::FLAC
+            ]
+        );
+    }
+}
@@ -1,4 +1,17 @@
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    errors::Error,
+    structs::{
+        ChannelSearchResult, ChapterInfo, ContinuationToken, HeatMarker, PlaylistInfo,
+        PlaylistSearchResult, PlaylistVideo, RelatedVideo, Thumbnails,
+    },
+};
 
 // Generated using https://transform.tools/json-to-rust-serde
 // Not public facing but are used instead of serde_json::Value
@@ -11,22 +24,46 @@ pub struct WebSearch {
 }
 
 impl WebSearch {
+    /// Returns the ids of the videos in the results.
     pub fn queries(&self) -> Vec<String> {
+        self.items()
+            .filter_map(|x| x.video_renderer.as_ref().map(|x| x.video_id.clone()))
+            .collect()
+    }
+
+    /// Returns the channels in the results.
+    pub fn channels(&self) -> Vec<ChannelSearchResult> {
+        self.items()
+            .filter_map(|x| x.channel_renderer.as_ref())
+            .map(|x| ChannelSearchResult {
+                channel_id: x.channel_id.clone(),
+                title: x.title.as_ref().map(Text::text).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Returns the playlists in the results.
+    pub fn playlists(&self) -> Vec<PlaylistSearchResult> {
+        self.items()
+            .filter_map(|x| x.playlist_renderer.as_ref())
+            .map(|x| PlaylistSearchResult {
+                playlist_id: x.playlist_id.clone(),
+                title: x.title.as_ref().map(Text::text).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Returns the items of the first item section, which holds the results.
+    fn items(&self) -> impl Iterator<Item = &Content2> {
         self.contents
             .two_column_search_results_renderer
             .primary_contents
             .section_list_renderer
             .contents
             .iter()
-            .find(|x| x.item_section_renderer.is_some())
-            .unwrap()
-            .item_section_renderer
-            .as_ref()
-            .unwrap()
-            .contents
-            .iter()
-            .filter_map(|x| x.video_renderer.as_ref().map(|x| x.video_id.to_string()))
-            .collect()
+            .find_map(|x| x.item_section_renderer.as_ref())
+            .into_iter()
+            .flat_map(|x| &x.contents)
     }
 }
 
@@ -70,6 +107,8 @@ struct ItemSectionRenderer {
 #[serde(rename_all = "camelCase")]
 struct Content2 {
     pub video_renderer: Option<VideoRenderer>,
+    pub channel_renderer: Option<ChannelRenderer>,
+    pub playlist_renderer: Option<PlaylistRenderer>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -77,3 +116,1317 @@ struct Content2 {
 struct VideoRenderer {
     pub video_id: String,
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelRenderer {
+    pub channel_id: String,
+    pub title: Option<Text>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistRenderer {
+    pub playlist_id: String,
+    pub title: Option<Text>,
+}
+
+/// Response of the browse endpoint for lists of videos, such as channel tabs, either the initial
+/// page or a continuation.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Browse {
+    contents: Option<BrowseContents>,
+    #[serde(default)]
+    on_response_received_actions: Vec<ResponseReceivedAction>,
+    header: Option<BrowseHeader>,
+    sidebar: Option<Sidebar>,
+    metadata: Option<BrowseMetadata>,
+}
+
+impl Browse {
+    fn items(&self) -> impl Iterator<Item = &BrowseItem> {
+        let tab_items = self
+            .contents
+            .iter()
+            .flat_map(|x| &x.two_column_browse_results_renderer.tabs)
+            .filter_map(|x| x.tab_renderer.as_ref())
+            .filter(|x| x.selected)
+            .filter_map(|x| x.content.as_ref())
+            .flat_map(|x| {
+                let rich = x.rich_grid_renderer.iter().flat_map(|x| &x.contents);
+                let grid = x
+                    .section_list_renderer
+                    .iter()
+                    .flat_map(|x| &x.contents)
+                    .filter_map(|x| x.item_section_renderer.as_ref())
+                    .flat_map(|x| &x.contents)
+                    .flat_map(|x| {
+                        let grid = x.grid_renderer.iter().flat_map(|x| &x.items);
+                        let shelf = x
+                            .shelf_renderer
+                            .iter()
+                            .filter_map(|x| x.content.expanded_shelf_contents_renderer.as_ref())
+                            .flat_map(|x| &x.items);
+                        let playlist = x
+                            .playlist_video_list_renderer
+                            .iter()
+                            .flat_map(|x| &x.contents);
+                        grid.chain(shelf).chain(playlist).chain(std::iter::once(x))
+                    });
+                rich.chain(grid)
+            });
+        let continuation_items = self
+            .on_response_received_actions
+            .iter()
+            .filter_map(|x| x.append_continuation_items_action.as_ref())
+            .flat_map(|x| &x.continuation_items);
+        tab_items.chain(continuation_items)
+    }
+
+    pub fn video_ids(&self) -> Vec<String> {
+        self.items()
+            .filter_map(|x| {
+                x.rich_item_renderer
+                    .as_ref()
+                    .and_then(|x| x.content.video_renderer.as_ref())
+                    .or(x.grid_video_renderer.as_ref())
+                    .or(x.video_renderer.as_ref())
+                    .or(x.playlist_video_renderer.as_ref().map(|x| &x.video))
+            })
+            .map(|x| x.video_id.clone())
+            .collect()
+    }
+
+    pub fn playlist_info(&self, playlist_id: &str) -> PlaylistInfo {
+        let header = self
+            .header
+            .as_ref()
+            .and_then(|x| x.playlist_header_renderer.as_ref());
+        let sidebar = self
+            .sidebar
+            .iter()
+            .flat_map(|x| &x.playlist_sidebar_renderer.items);
+        let primary = sidebar
+            .clone()
+            .find_map(|x| x.playlist_sidebar_primary_info_renderer.as_ref());
+        let secondary = sidebar
+            .clone()
+            .find_map(|x| x.playlist_sidebar_secondary_info_renderer.as_ref());
+        let metadata = self
+            .metadata
+            .as_ref()
+            .and_then(|x| x.playlist_metadata_renderer.as_ref());
+
+        let title = metadata
+            .map(|x| x.title.clone())
+            .or_else(|| header.and_then(|x| x.title.as_ref()).map(Text::text))
+            .or_else(|| primary.and_then(|x| x.title.as_ref()).map(Text::text))
+            .unwrap_or_default();
+        let description = metadata
+            .and_then(|x| x.description.clone())
+            .or_else(|| {
+                header
+                    .and_then(|x| x.description_text.as_ref())
+                    .map(Text::text)
+            })
+            .or_else(|| primary.and_then(|x| x.description.as_ref()).map(Text::text))
+            .unwrap_or_default();
+        let author = secondary
+            .and_then(|x| x.video_owner.as_ref())
+            .map(|x| x.video_owner_renderer.title.text())
+            .or_else(|| header.and_then(|x| x.owner_text.as_ref()).map(Text::text));
+        let thumbnails = primary
+            .and_then(|x| x.thumbnail_renderer.as_ref())
+            .and_then(|x| x.playlist_video_thumbnail_renderer.as_ref())
+            .map(|x| x.thumbnail.thumbnails.clone())
+            .unwrap_or_default();
+
+        // Stats are in the form of: "100 videos", "1,234 views", "Last updated on Jan 1, 2024",
+        // always in this order but localized
+        let stats: Vec<String> = primary
+            .map(|x| x.stats.iter().map(Text::text).collect())
+            .or_else(|| header.map(|x| x.stats.iter().map(Text::text).collect()))
+            .unwrap_or_default();
+        let video_count = header
+            .and_then(|x| x.num_videos_text.as_ref())
+            .map(Text::text)
+            .or_else(|| stats.first().cloned())
+            .and_then(|x| parse_count(&x));
+        let last_updated = stats.into_iter().nth(2);
+
+        PlaylistInfo {
+            playlist_id: playlist_id.to_owned(),
+            title,
+            description,
+            author,
+            thumbnails,
+            video_count,
+            last_updated,
+            videos: self.playlist_videos(),
+        }
+    }
+
+    fn playlist_videos(&self) -> Vec<PlaylistVideo> {
+        self.items()
+            .filter_map(|x| x.playlist_video_renderer.as_ref())
+            .map(|x| PlaylistVideo {
+                video_id: x.video.video_id.clone(),
+                title: x.title.as_ref().map(Text::text).unwrap_or_default(),
+                length_seconds: x.length_seconds.as_deref().and_then(|x| x.parse().ok()),
+                is_playable: x.is_playable,
+            })
+            .collect()
+    }
+
+    pub fn continuation(&self) -> Option<ContinuationToken> {
+        self.items()
+            .filter_map(|x| x.continuation_item_renderer.as_ref())
+            .map(|x| ContinuationToken(x.continuation_endpoint.continuation_command.token.clone()))
+            .next()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchNext {
+    contents: Option<WatchNextContents>,
+    #[serde(default)]
+    engagement_panels: Vec<EngagementPanel>,
+    player_overlays: Option<PlayerOverlays>,
+    framework_updates: Option<FrameworkUpdates>,
+}
+
+impl WatchNext {
+    pub fn related_videos(&self) -> Vec<RelatedVideo> {
+        self.contents
+            .iter()
+            .filter_map(|x| x.two_column_watch_next_results.secondary_results.as_ref())
+            .flat_map(|x| &x.secondary_results.results)
+            .filter_map(|x| x.compact_video_renderer.as_ref())
+            .map(|x| RelatedVideo {
+                video_id: x.video_id.clone(),
+                title: x.title.as_ref().map(Text::text).unwrap_or_default(),
+                channel_name: x
+                    .long_byline_text
+                    .as_ref()
+                    .or(x.short_byline_text.as_ref())
+                    .map(Text::text)
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Returns the handle of the channel which uploaded the video, such as `"@RickAstleyYT"`.
+    pub fn channel_handle(&self) -> Option<String> {
+        self.video_owner()?
+            .navigation_endpoint
+            .as_ref()?
+            .browse_endpoint
+            .canonical_base_url
+            .as_deref()?
+            .strip_prefix('/')
+            .filter(|x| x.starts_with('@'))
+            .map(str::to_owned)
+    }
+
+    /// Returns the avatar of the channel which uploaded the video.
+    pub fn channel_thumbnails(&self) -> Option<Thumbnails> {
+        self.video_owner()?.thumbnail.clone()
+    }
+
+    fn video_owner(&self) -> Option<&VideoOwnerRenderer> {
+        self.contents
+            .iter()
+            .filter_map(|x| x.two_column_watch_next_results.results.as_ref())
+            .flat_map(|x| &x.results.contents)
+            .filter_map(|x| x.video_secondary_info_renderer.as_ref())
+            .find_map(|x| x.owner.as_ref())
+            .map(|x| &x.video_owner_renderer)
+    }
+
+    /// Heat markers are either part of the framework updates (newer) or the player bar (older),
+    /// prefer the former.
+    pub fn heat_markers(&self) -> Vec<HeatMarker> {
+        let entity_markers = self
+            .framework_updates
+            .iter()
+            .flat_map(|x| &x.entity_batch_update.mutations)
+            .filter_map(|x| x.payload.as_ref()?.macro_markers_list_entity.as_ref())
+            .filter(|x| x.markers_list.marker_type.as_deref() == Some("MARKER_TYPE_HEATMAP"))
+            .flat_map(|x| &x.markers_list.markers)
+            .filter_map(|x| {
+                heat_marker(
+                    &x.start_millis,
+                    &x.duration_millis,
+                    &x.intensity_score_normalized,
+                )
+            })
+            .collect::<Vec<HeatMarker>>();
+        if !entity_markers.is_empty() {
+            return entity_markers;
+        }
+
+        self.player_overlays
+            .iter()
+            .filter_map(|x| {
+                x.player_overlay_renderer
+                    .decorated_player_bar_renderer
+                    .as_ref()
+            })
+            .filter_map(|x| {
+                x.decorated_player_bar_renderer
+                    .player_bar
+                    .multi_markers_player_bar_renderer
+                    .as_ref()
+            })
+            .flat_map(|x| &x.markers_map)
+            .filter_map(|x| x.value.heatmap.as_ref())
+            .flat_map(|x| &x.heatmap_renderer.heat_markers)
+            .map(|x| &x.heat_marker_renderer)
+            .filter_map(|x| {
+                heat_marker(
+                    &x.time_range_start_millis,
+                    &x.marker_duration_millis,
+                    &x.heat_marker_intensity_score_normalized,
+                )
+            })
+            .collect()
+    }
+
+    /// Chapters are taken from the chapters panel, falling back to timestamps in the description
+    /// if the panel is missing.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn chapters(&self) -> Vec<ChapterInfo> {
+        let chapters = self
+            .engagement_panels
+            .iter()
+            .map(|x| &x.engagement_panel_section_list_renderer)
+            .filter(|x| !matches!(x.target_id.as_deref(), Some(id) if !id.ends_with("chapters")))
+            .filter_map(|x| x.content.as_ref()?.macro_markers_list_renderer.as_ref())
+            .flat_map(|x| &x.contents)
+            .filter_map(|x| x.macro_markers_list_item_renderer.as_ref())
+            .filter_map(|x| {
+                let start_seconds = x
+                    .on_tap
+                    .as_ref()
+                    .and_then(|x| as_f64(&x.watch_endpoint.start_time_seconds))
+                    .map(|x| x as u64)
+                    .or_else(|| parse_timestamp(&x.time_description.as_ref()?.text()))?;
+                Some(ChapterInfo {
+                    title: x.title.as_ref().map(Text::text).unwrap_or_default(),
+                    start_ms: start_seconds * 1000,
+                    thumbnail_url: x
+                        .thumbnail
+                        .as_ref()
+                        .and_then(|x| x.thumbnails.last())
+                        .map(|x| x.url.clone()),
+                })
+            })
+            .collect::<Vec<ChapterInfo>>();
+        if !chapters.is_empty() {
+            return chapters;
+        }
+
+        self.description()
+            .map(|x| parse_chapters(&x))
+            .unwrap_or_default()
+    }
+
+    fn description(&self) -> Option<String> {
+        self.contents
+            .iter()
+            .filter_map(|x| x.two_column_watch_next_results.results.as_ref())
+            .flat_map(|x| &x.results.contents)
+            .filter_map(|x| x.video_secondary_info_renderer.as_ref())
+            .find_map(|x| x.attributed_description.as_ref())
+            .map(|x| x.content.clone())
+    }
+}
+
+/// Parses chapters from timestamps at the start of description lines, such as
+/// `01:23 Chapter title`. Like YouTube, this requires at least three chapters in ascending order
+/// with the first starting at 0:00.
+fn parse_chapters(description: &str) -> Vec<ChapterInfo> {
+    static CHAPTER: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^\W*?((?:\d{1,2}:)?\d{1,2}:\d{2})\s*[-–:|]?\s*(.+)$").unwrap());
+
+    let chapters: Vec<ChapterInfo> = description
+        .lines()
+        .filter_map(|line| {
+            let captures = CHAPTER.captures(line.trim())?;
+            Some(ChapterInfo {
+                title: captures[2].trim().to_owned(),
+                start_ms: parse_timestamp(&captures[1])? * 1000,
+                thumbnail_url: None,
+            })
+        })
+        .collect();
+
+    let ascending = chapters.windows(2).all(|x| x[0].start_ms < x[1].start_ms);
+    if chapters.len() < 3 || chapters[0].start_ms != 0 || !ascending {
+        return Vec::new();
+    }
+    chapters
+}
+
+/// Parses timestamps such as `1:02:03` or `02:03` into seconds.
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let mut seconds = 0;
+    for part in timestamp.trim().split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// Parse the search suggestions out of a JSONP response, which looks like:
+/// `window.google.ac.h(["query",[["suggestion",0,[512]],...],{...}])`.
+///
+/// Fails if the response is not a json array with or without the callback, such as an html
+/// page shown when requests are blocked.
+pub fn parse_suggestions(jsonp: &str) -> Result<Vec<String>, Error> {
+    // Strip the callback, what remains is a plain json array
+    let json = match (jsonp.find('('), jsonp.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &jsonp[start + 1..end],
+        _ => jsonp,
+    };
+    let Value::Array(values) = serde_json::from_str::<Value>(json)? else {
+        return Err(Error::Unexpected(
+            "search suggestions are not a json array".to_owned(),
+        ));
+    };
+
+    Ok(values
+        .get(1)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.get(0)?.as_str().map(ToOwned::to_owned))
+        .collect())
+}
+
+/// Parse the leading number of a count text such as "1,234 videos", or an abbreviated one such
+/// as "1.2K views".
+fn parse_count(text: &str) -> Option<u64> {
+    let text = text.trim_start();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+        .unwrap_or(text.len());
+    let (number, suffix) = text.split_at(end);
+    let multiplier: u64 = match suffix.chars().next() {
+        Some('K') => 1_000,
+        Some('M') => 1_000_000,
+        Some('B') => 1_000_000_000,
+        // Separators only group the digits of whole counts
+        _ => {
+            let digits: String = number.chars().filter(char::is_ascii_digit).collect();
+            return digits.parse().ok();
+        }
+    };
+
+    // Abbreviated counts have a decimal separator instead
+    let (whole, fraction) = number.split_once(['.', ',']).unwrap_or((number, ""));
+    let whole: u64 = whole.parse().ok()?;
+    let scale = 10u64.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse().ok()?
+    };
+    Some(whole * multiplier + fraction * multiplier / scale)
+}
+
+/// Text is either given as a whole or split into runs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Text {
+    simple_text: Option<String>,
+    runs: Option<Vec<TextRun>>,
+}
+
+impl Text {
+    pub(crate) fn text(&self) -> String {
+        if let Some(text) = &self.simple_text {
+            return text.clone();
+        }
+        self.runs
+            .iter()
+            .flatten()
+            .map(|x| x.text.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextRun {
+    text: String,
+}
+
+/// Values can be either numbers or strings depending on where they come from.
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(x) => x.as_f64(),
+        Value::String(x) => x.parse().ok(),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn heat_marker(start: &Value, duration: &Value, intensity: &Value) -> Option<HeatMarker> {
+    Some(HeatMarker {
+        start: Duration::from_millis(as_f64(start)? as u64),
+        duration: Duration::from_millis(as_f64(duration)? as u64),
+        intensity: as_f64(intensity)? as f32,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseContents {
+    two_column_browse_results_renderer: TwoColumnBrowseResultsRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TwoColumnBrowseResultsRenderer {
+    #[serde(default)]
+    tabs: Vec<Tab>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tab {
+    tab_renderer: Option<TabRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabRenderer {
+    #[serde(default)]
+    selected: bool,
+    content: Option<TabContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabContent {
+    rich_grid_renderer: Option<RichGridRenderer>,
+    section_list_renderer: Option<BrowseSectionListRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RichGridRenderer {
+    #[serde(default)]
+    contents: Vec<BrowseItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseSectionListRenderer {
+    #[serde(default)]
+    contents: Vec<BrowseSection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseSection {
+    item_section_renderer: Option<BrowseItemSectionRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseItemSectionRenderer {
+    #[serde(default)]
+    contents: Vec<BrowseItem>,
+}
+
+/// Any of the items which may appear in a list of videos, most of these are mutually exclusive.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseItem {
+    rich_item_renderer: Option<RichItemRenderer>,
+    grid_video_renderer: Option<VideoRenderer>,
+    video_renderer: Option<VideoRenderer>,
+    grid_renderer: Option<GridRenderer>,
+    shelf_renderer: Option<ShelfRenderer>,
+    playlist_video_list_renderer: Option<PlaylistVideoListRenderer>,
+    playlist_video_renderer: Option<PlaylistVideoRenderer>,
+    continuation_item_renderer: Option<ContinuationItemRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoListRenderer {
+    #[serde(default)]
+    contents: Vec<BrowseItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoRenderer {
+    #[serde(flatten)]
+    video: VideoRenderer,
+    title: Option<Text>,
+    length_seconds: Option<String>,
+    #[serde(default)]
+    is_playable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseHeader {
+    playlist_header_renderer: Option<PlaylistHeaderRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistHeaderRenderer {
+    title: Option<Text>,
+    description_text: Option<Text>,
+    owner_text: Option<Text>,
+    num_videos_text: Option<Text>,
+    #[serde(default)]
+    stats: Vec<Text>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sidebar {
+    playlist_sidebar_renderer: PlaylistSidebarRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSidebarRenderer {
+    #[serde(default)]
+    items: Vec<PlaylistSidebarItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSidebarItem {
+    playlist_sidebar_primary_info_renderer: Option<PlaylistSidebarPrimaryInfoRenderer>,
+    playlist_sidebar_secondary_info_renderer: Option<PlaylistSidebarSecondaryInfoRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSidebarPrimaryInfoRenderer {
+    title: Option<Text>,
+    description: Option<Text>,
+    #[serde(default)]
+    stats: Vec<Text>,
+    thumbnail_renderer: Option<PlaylistThumbnailRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistThumbnailRenderer {
+    playlist_video_thumbnail_renderer: Option<PlaylistVideoThumbnailRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoThumbnailRenderer {
+    thumbnail: Thumbnails,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSidebarSecondaryInfoRenderer {
+    video_owner: Option<VideoOwner>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoOwner {
+    video_owner_renderer: VideoOwnerRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoOwnerRenderer {
+    title: Text,
+    thumbnail: Option<Thumbnails>,
+    navigation_endpoint: Option<OwnerEndpoint>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnerEndpoint {
+    browse_endpoint: OwnerBrowseEndpoint,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnerBrowseEndpoint {
+    canonical_base_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseMetadata {
+    playlist_metadata_renderer: Option<PlaylistMetadataRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistMetadataRenderer {
+    title: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RichItemRenderer {
+    content: RichItemContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RichItemContent {
+    video_renderer: Option<VideoRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GridRenderer {
+    #[serde(default)]
+    items: Vec<BrowseItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShelfRenderer {
+    content: ShelfContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShelfContent {
+    expanded_shelf_contents_renderer: Option<GridRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinuationItemRenderer {
+    continuation_endpoint: ContinuationEndpoint,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinuationEndpoint {
+    continuation_command: ContinuationCommand,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinuationCommand {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseReceivedAction {
+    append_continuation_items_action: Option<AppendContinuationItemsAction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppendContinuationItemsAction {
+    #[serde(default)]
+    continuation_items: Vec<BrowseItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchNextContents {
+    two_column_watch_next_results: TwoColumnWatchNextResults,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TwoColumnWatchNextResults {
+    results: Option<PrimaryResults>,
+    secondary_results: Option<SecondaryResults>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrimaryResults {
+    results: PrimaryResults2,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrimaryResults2 {
+    #[serde(default)]
+    contents: Vec<PrimaryResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrimaryResult {
+    video_secondary_info_renderer: Option<VideoSecondaryInfoRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoSecondaryInfoRenderer {
+    owner: Option<VideoOwner>,
+    attributed_description: Option<AttributedDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributedDescription {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngagementPanel {
+    engagement_panel_section_list_renderer: EngagementPanelSectionListRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngagementPanelSectionListRenderer {
+    target_id: Option<String>,
+    content: Option<EngagementPanelContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngagementPanelContent {
+    macro_markers_list_renderer: Option<MacroMarkersListRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkersListRenderer {
+    #[serde(default)]
+    contents: Vec<MacroMarkersListItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkersListItem {
+    macro_markers_list_item_renderer: Option<MacroMarkersListItemRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkersListItemRenderer {
+    title: Option<Text>,
+    time_description: Option<Text>,
+    thumbnail: Option<Thumbnails>,
+    on_tap: Option<MacroMarkerOnTap>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkerOnTap {
+    watch_endpoint: MacroMarkerWatchEndpoint,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkerWatchEndpoint {
+    #[serde(default)]
+    start_time_seconds: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecondaryResults {
+    secondary_results: SecondaryResults2,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecondaryResults2 {
+    #[serde(default)]
+    results: Vec<SecondaryResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecondaryResult {
+    compact_video_renderer: Option<CompactVideoRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompactVideoRenderer {
+    video_id: String,
+    title: Option<Text>,
+    long_byline_text: Option<Text>,
+    short_byline_text: Option<Text>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameworkUpdates {
+    entity_batch_update: EntityBatchUpdate,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EntityBatchUpdate {
+    #[serde(default)]
+    mutations: Vec<Mutation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Mutation {
+    payload: Option<Payload>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload {
+    macro_markers_list_entity: Option<MacroMarkersListEntity>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MacroMarkersListEntity {
+    markers_list: MarkersList,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkersList {
+    marker_type: Option<String>,
+    #[serde(default)]
+    markers: Vec<Marker>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Marker {
+    start_millis: Value,
+    duration_millis: Value,
+    intensity_score_normalized: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerOverlays {
+    player_overlay_renderer: PlayerOverlayRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerOverlayRenderer {
+    decorated_player_bar_renderer: Option<DecoratedPlayerBarRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DecoratedPlayerBarRenderer {
+    decorated_player_bar_renderer: DecoratedPlayerBarRenderer2,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DecoratedPlayerBarRenderer2 {
+    player_bar: PlayerBar,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerBar {
+    multi_markers_player_bar_renderer: Option<MultiMarkersPlayerBarRenderer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MultiMarkersPlayerBarRenderer {
+    #[serde(default)]
+    markers_map: Vec<MarkersMap>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkersMap {
+    value: MarkersValue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkersValue {
+    heatmap: Option<Heatmap>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Heatmap {
+    heatmap_renderer: HeatmapRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeatmapRenderer {
+    #[serde(default)]
+    heat_markers: Vec<HeatMarkers>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeatMarkers {
+    heat_marker_renderer: HeatMarkerRenderer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeatMarkerRenderer {
+    time_range_start_millis: Value,
+    marker_duration_millis: Value,
+    heat_marker_intensity_score_normalized: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let jsonp = r#"window.google.ac.h(["rust",[["rust",0,[512,433]],["rust game",0,[512,433]],["rust (programming)",0,[512]]],{"k":1,"q":"abc"}])"#;
+        assert_eq!(
+            parse_suggestions(jsonp).unwrap(),
+            ["rust", "rust game", "rust (programming)"]
+        );
+        assert_eq!(
+            parse_suggestions("window.google.ac.h([\"a\",[[\"a b\",0]]]);\n").unwrap(),
+            ["a b"]
+        );
+        assert!(parse_suggestions(r#"["a",[]]"#).unwrap().is_empty());
+        assert!(matches!(
+            parse_suggestions("window.google.ac.h("),
+            Err(Error::Json(_))
+        ));
+        assert!(matches!(
+            parse_suggestions("<html><body>Sorry...</body></html>"),
+            Err(Error::Json(_))
+        ));
+        assert!(matches!(
+            parse_suggestions(r#"{"error": 429}"#),
+            Err(Error::Unexpected(_))
+        ));
+    }
+
+    #[test]
+    fn test_web_search() {
+        let json = r#"{"contents": {"twoColumnSearchResultsRenderer": {"primaryContents": {
+            "sectionListRenderer": {"contents": [
+                {"itemSectionRenderer": {"contents": [
+                    {"channelRenderer": {
+                        "channelId": "UCuAXFkgsw1L7xaCfnd5JJOw",
+                        "title": {"simpleText": "Rick Astley"}
+                    }},
+                    {"videoRenderer": {"videoId": "dQw4w9WgXcQ"}},
+                    {"playlistRenderer": {
+                        "playlistId": "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI",
+                        "title": {"simpleText": "Greatest Hits"}
+                    }},
+                    {"playlistRenderer": {"playlistId": "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"}},
+                    {"shelfRenderer": {}}
+                ]}},
+                {"continuationItemRenderer": {}}
+            ]}
+        }}}}"#;
+        let search: WebSearch = serde_json::from_str(json).unwrap();
+        assert_eq!(search.queries(), ["dQw4w9WgXcQ"]);
+        assert_eq!(
+            search.channels(),
+            [ChannelSearchResult {
+                channel_id: "UCuAXFkgsw1L7xaCfnd5JJOw".to_owned(),
+                title: "Rick Astley".to_owned(),
+            }]
+        );
+        assert_eq!(
+            search.playlists(),
+            [
+                PlaylistSearchResult {
+                    playlist_id: "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI".to_owned(),
+                    title: "Greatest Hits".to_owned(),
+                },
+                PlaylistSearchResult {
+                    playlist_id: "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG".to_owned(),
+                    title: String::new(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_browse_videos() {
+        let json = r#"{
+            "contents": {"twoColumnBrowseResultsRenderer": {"tabs": [
+                {"tabRenderer": {"selected": false}},
+                {"tabRenderer": {"selected": true, "content": {"richGridRenderer": {"contents": [
+                    {"richItemRenderer": {"content": {"videoRenderer": {"videoId": "NLqAF9hrVbY"}}}},
+                    {"richItemRenderer": {"content": {"videoRenderer": {"videoId": "IB3lcPjvWLA"}}}},
+                    {"continuationItemRenderer": {"continuationEndpoint": {
+                        "continuationCommand": {"token": "4qmFsgKrCBIYVUN1"}
+                    }}}
+                ]}}}}
+            ]}}
+        }"#;
+        let browse: Browse = serde_json::from_str(json).unwrap();
+        assert_eq!(browse.video_ids(), ["NLqAF9hrVbY", "IB3lcPjvWLA"]);
+        assert_eq!(
+            browse.continuation().map(|x| x.to_string()).as_deref(),
+            Some("4qmFsgKrCBIYVUN1")
+        );
+    }
+
+    #[test]
+    fn test_browse_shelf() {
+        let json = r#"{
+            "contents": {"twoColumnBrowseResultsRenderer": {"tabs": [
+                {"tabRenderer": {"selected": true, "content": {"sectionListRenderer": {"contents": [
+                    {"itemSectionRenderer": {"contents": [{"shelfRenderer": {"content": {
+                        "expandedShelfContentsRenderer": {"items": [
+                            {"videoRenderer": {"videoId": "a9LDPn-MO4I"}}
+                        ]}
+                    }}}]}}
+                ]}}}}
+            ]}}
+        }"#;
+        let browse: Browse = serde_json::from_str(json).unwrap();
+        assert_eq!(browse.video_ids(), ["a9LDPn-MO4I"]);
+    }
+
+    #[test]
+    fn test_browse_playlist() {
+        let json = r#"{
+            "contents": {"twoColumnBrowseResultsRenderer": {"tabs": [
+                {"tabRenderer": {"selected": true, "content": {"sectionListRenderer": {"contents": [
+                    {"itemSectionRenderer": {"contents": [{"playlistVideoListRenderer": {"contents": [
+                        {"playlistVideoRenderer": {
+                            "videoId": "NLqAF9hrVbY",
+                            "title": {"runs": [{"text": "First"}]},
+                            "lengthSeconds": "213",
+                            "isPlayable": true
+                        }},
+                        {"playlistVideoRenderer": {
+                            "videoId": "IB3lcPjvWLA",
+                            "title": {"runs": [{"text": "[Private video]"}]},
+                            "isPlayable": false
+                        }}
+                    ]}}]}}
+                ]}}}}
+            ]}},
+            "sidebar": {"playlistSidebarRenderer": {"items": [
+                {"playlistSidebarPrimaryInfoRenderer": {
+                    "title": {"runs": [{"text": "A playlist"}]},
+                    "stats": [
+                        {"runs": [{"text": "1,234"}, {"text": " videos"}]},
+                        {"simpleText": "5,678 views"},
+                        {"runs": [{"text": "Last updated on "}, {"text": "Jan 1, 2024"}]}
+                    ],
+                    "thumbnailRenderer": {"playlistVideoThumbnailRenderer": {"thumbnail": {
+                        "thumbnails": [{"url": "https://i.ytimg.com/vi/NLqAF9hrVbY/hqdefault.jpg", "width": 480, "height": 270}]
+                    }}}
+                }},
+                {"playlistSidebarSecondaryInfoRenderer": {"videoOwner": {"videoOwnerRenderer": {
+                    "title": {"runs": [{"text": "A channel"}]}
+                }}}}
+            ]}},
+            "metadata": {"playlistMetadataRenderer": {"title": "A playlist", "description": "Some videos"}}
+        }"#;
+        let browse: Browse = serde_json::from_str(json).unwrap();
+        let info = browse.playlist_info("PLBCF2DAC6FFB574DE");
+        assert_eq!(info.title, "A playlist");
+        assert_eq!(info.description, "Some videos");
+        assert_eq!(info.author.as_deref(), Some("A channel"));
+        assert_eq!(info.video_count, Some(1234));
+        assert_eq!(
+            info.last_updated.as_deref(),
+            Some("Last updated on Jan 1, 2024")
+        );
+        assert_eq!(info.thumbnails.len(), 1);
+        assert_eq!(info.videos.len(), 2);
+        assert_eq!(info.videos[0].length_seconds, Some(213));
+        assert!(info.videos[0].is_playable);
+        assert!(!info.videos[1].is_playable);
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("1,234 videos"), Some(1234));
+        assert_eq!(parse_count(" 1.234 Videos"), Some(1234));
+        assert_eq!(parse_count("1.2K views"), Some(1200));
+        assert_eq!(parse_count("15K views"), Some(15_000));
+        assert_eq!(parse_count("3.45M views"), Some(3_450_000));
+        assert_eq!(parse_count("1B views"), Some(1_000_000_000));
+        assert_eq!(parse_count("No views"), None);
+    }
+
+    #[test]
+    fn test_browse_continuation() {
+        let json = r#"{
+            "onResponseReceivedActions": [{"appendContinuationItemsAction": {"continuationItems": [
+                {"gridVideoRenderer": {"videoId": "BaW_jenozKc"}}
+            ]}}]
+        }"#;
+        let browse: Browse = serde_json::from_str(json).unwrap();
+        assert_eq!(browse.video_ids(), ["BaW_jenozKc"]);
+        assert!(browse.continuation().is_none());
+    }
+
+    #[test]
+    fn test_heat_markers() {
+        let json = r#"{
+            "frameworkUpdates": {"entityBatchUpdate": {"mutations": [
+                {"payload": {"macroMarkersListEntity": {"markersList": {
+                    "markerType": "MARKER_TYPE_HEATMAP",
+                    "markers": [
+                        {"startMillis": "0", "durationMillis": "2500", "intensityScoreNormalized": 1},
+                        {"startMillis": "2500", "durationMillis": "2500", "intensityScoreNormalized": 0.25}
+                    ]
+                }}}}
+            ]}}
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        let markers = next.heat_markers();
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[1].start, Duration::from_millis(2500));
+        assert_eq!(markers[1].duration, Duration::from_millis(2500));
+        assert!((markers[1].intensity - 0.25).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_related_videos() {
+        let json = r#"{
+            "contents": {"twoColumnWatchNextResults": {"secondaryResults": {"secondaryResults": {
+                "results": [
+                    {"compactVideoRenderer": {
+                        "videoId": "NLqAF9hrVbY",
+                        "title": {"simpleText": "A title"},
+                        "longBylineText": {"runs": [{"text": "A channel"}]}
+                    }},
+                    {"continuationItemRenderer": {}}
+                ]
+            }}}}
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        let related = next.related_videos();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].video_id, "NLqAF9hrVbY");
+        assert_eq!(related[0].title, "A title");
+        assert_eq!(related[0].channel_name, "A channel");
+    }
+
+    #[test]
+    fn test_video_owner() {
+        let json = r#"{
+            "contents": {"twoColumnWatchNextResults": {"results": {"results": {"contents": [
+                {"videoPrimaryInfoRenderer": {}},
+                {"videoSecondaryInfoRenderer": {"owner": {"videoOwnerRenderer": {
+                    "thumbnail": {"thumbnails": [{"url": "https://yt3.ggpht.com/avatar=s48", "width": 48, "height": 48}]},
+                    "title": {"runs": [{"text": "Rick Astley"}]},
+                    "navigationEndpoint": {"browseEndpoint": {
+                        "browseId": "UCuAXFkgsw1L7xaCfnd5JJOw",
+                        "canonicalBaseUrl": "/@RickAstleyYT"
+                    }}
+                }}}}
+            ]}}}}
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        assert_eq!(next.channel_handle().as_deref(), Some("@RickAstleyYT"));
+        let thumbnails = next.channel_thumbnails().unwrap().thumbnails;
+        assert_eq!(thumbnails[0].url, "https://yt3.ggpht.com/avatar=s48");
+
+        let next: WatchNext = serde_json::from_str("{}").unwrap();
+        assert_eq!(next.channel_handle(), None);
+        assert_eq!(next.channel_thumbnails(), None);
+    }
+
+    #[test]
+    fn test_chapters() {
+        let json = r#"{
+            "engagementPanels": [
+                {"engagementPanelSectionListRenderer": {"targetId": "engagement-panel-structured-description"}},
+                {"engagementPanelSectionListRenderer": {
+                    "targetId": "engagement-panel-macro-markers-description-chapters",
+                    "content": {"macroMarkersListRenderer": {"contents": [
+                        {"macroMarkersListItemRenderer": {
+                            "title": {"simpleText": "Intro"},
+                            "timeDescription": {"simpleText": "0:00"},
+                            "thumbnail": {"thumbnails": [{"url": "https://i.ytimg.com/vi/x/hqdefault_0.jpg", "width": 168, "height": 94}]},
+                            "onTap": {"watchEndpoint": {"videoId": "x"}}
+                        }},
+                        {"macroMarkersListItemRenderer": {
+                            "title": {"simpleText": "Chorus"},
+                            "timeDescription": {"simpleText": "1:05"},
+                            "onTap": {"watchEndpoint": {"videoId": "x", "startTimeSeconds": 65}}
+                        }}
+                    ]}}
+                }}
+            ]
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        let chapters = next.chapters();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title, "Intro");
+        assert_eq!(chapters[0].start_ms, 0);
+        assert_eq!(
+            chapters[0].thumbnail_url.as_deref(),
+            Some("https://i.ytimg.com/vi/x/hqdefault_0.jpg")
+        );
+        assert_eq!(chapters[1].start_ms, 65_000);
+        assert_eq!(chapters[1].thumbnail_url, None);
+
+        let json = r#"{
+            "contents": {"twoColumnWatchNextResults": {"results": {"results": {"contents": [
+                {"videoSecondaryInfoRenderer": {"attributedDescription": {
+                    "content": "A video\n\n0:00 Intro\n- 1:30 - Verse\n1:02:03 | The end\nnot 4:00 a chapter"
+                }}}
+            ]}}}}
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        let chapters = next.chapters();
+        let titles: Vec<&str> = chapters.iter().map(|x| x.title.as_str()).collect();
+        assert_eq!(titles, ["Intro", "Verse", "The end"]);
+        assert_eq!(chapters[2].start_ms, 3_723_000);
+
+        let next: WatchNext = serde_json::from_str("{}").unwrap();
+        assert!(next.chapters().is_empty());
+    }
+
+    #[test]
+    fn test_parse_chapters_invalid() {
+        // must start at 0:00
+        assert!(parse_chapters("0:10 a\n1:00 b\n2:00 c").is_empty());
+        // must be ascending
+        assert!(parse_chapters("0:00 a\n2:00 b\n1:00 c").is_empty());
+        // must have three chapters
+        assert!(parse_chapters("0:00 a\n1:00 b").is_empty());
+    }
+
+    #[test]
+    fn test_heat_markers_player_bar() {
+        let json = r#"{
+            "playerOverlays": {"playerOverlayRenderer": {"decoratedPlayerBarRenderer": {
+                "decoratedPlayerBarRenderer": {"playerBar": {"multiMarkersPlayerBarRenderer": {
+                    "markersMap": [{"value": {"heatmap": {"heatmapRenderer": {"heatMarkers": [
+                        {"heatMarkerRenderer": {
+                            "timeRangeStartMillis": 0,
+                            "markerDurationMillis": 1000,
+                            "heatMarkerIntensityScoreNormalized": 0.5
+                        }}
+                    ]}}}}]
+                }}}
+            }}}
+        }"#;
+        let next: WatchNext = serde_json::from_str(json).unwrap();
+        assert_eq!(next.heat_markers().len(), 1);
+    }
+}
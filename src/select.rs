@@ -0,0 +1,544 @@
+use std::cmp::Ordering;
+
+use crate::{
+    mime::{Acodec, Format, Vcodec},
+    structs::{Quality, Video, VideoFormat},
+};
+
+/// Builder for declaratively picking formats of a [`Video`].
+///
+/// Each constraint narrows down the formats considered, constraints are never relaxed so a
+/// selector which cannot be satisfied simply yields no formats.
+///
+/// ```no_run
+/// # use yinfo::{select::FormatSelector, Format, Vcodec, Video};
+/// # fn run(video: &Video) {
+/// let format = FormatSelector::new()
+///     .container(Format::Webm)
+///     .vcodec(Vcodec::VP9)
+///     .max_height(720)
+///     .best(video);
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FormatSelector {
+    container: Option<Format>,
+    vcodec: Option<Vcodec>,
+    acodec: Option<Acodec>,
+    acodec_order: Option<Vec<Acodec>>,
+    vcodec_order: Option<Vec<Vcodec>>,
+    max_height: Option<u32>,
+    min_audio_bitrate: Option<u32>,
+    exclude_drc: bool,
+    prefer_drc: bool,
+    hdr: Option<bool>,
+}
+
+impl FormatSelector {
+    /// Creates a selector without any constraints, matching every format.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only select formats with the given container.
+    #[must_use]
+    pub fn container(mut self, container: Format) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Only select formats with the given video codec, this excludes audio-only formats.
+    #[must_use]
+    pub fn vcodec(mut self, vcodec: Vcodec) -> Self {
+        self.vcodec = Some(vcodec);
+        self
+    }
+
+    /// Only select formats with the given audio codec, this excludes video-only formats.
+    #[must_use]
+    pub fn acodec(mut self, acodec: Acodec) -> Self {
+        self.acodec = Some(acodec);
+        self
+    }
+
+    /// Prefers video codecs in the given order in [`Self::best()`], the first being the most
+    /// preferred. Codecs not in the list are ranked last.
+    ///
+    /// By default codecs are not considered, see [`FormatPreferences::vcodec_order()`].
+    #[must_use]
+    pub fn vcodec_order(mut self, order: Vec<Vcodec>) -> Self {
+        self.vcodec_order = Some(order);
+        self
+    }
+
+    /// Prefers audio codecs in the given order in [`Self::best()`], the first being the most
+    /// preferred. Codecs not in the list are ranked last.
+    ///
+    /// By default codecs are not considered, see [`FormatPreferences::acodec_order()`].
+    #[must_use]
+    pub fn acodec_order(mut self, order: Vec<Acodec>) -> Self {
+        self.acodec_order = Some(order);
+        self
+    }
+
+    /// Only select formats with a height of at most the given pixels. Formats without video are
+    /// not affected.
+    #[must_use]
+    pub fn max_height(mut self, height: u32) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Only select formats with audio with a bitrate of at least the given bits per second, this
+    /// excludes video-only formats.
+    #[must_use]
+    pub fn min_audio_bitrate(mut self, bitrate: u32) -> Self {
+        self.min_audio_bitrate = Some(bitrate);
+        self
+    }
+
+    /// Exclude formats with dynamic range compression.
+    #[must_use]
+    pub fn exclude_drc(mut self) -> Self {
+        self.exclude_drc = true;
+        self
+    }
+
+    /// Prefer formats with dynamic range compression over otherwise identical formats without in
+    /// [`Self::best()`] if `true`. By default formats without are preferred.
+    #[must_use]
+    pub fn prefer_drc(mut self, prefer: bool) -> Self {
+        self.prefer_drc = prefer;
+        self
+    }
+
+    /// Only select HDR formats if `true`, otherwise only SDR formats, this excludes audio-only
+    /// formats. See [`VideoFormat::is_hdr()`].
+    #[must_use]
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.hdr = Some(hdr);
+        self
+    }
+
+    /// Returns whether the format satisfies every constraint of this selector.
+    #[must_use]
+    pub fn matches(&self, format: &VideoFormat) -> bool {
+        let mime = &format.mime_type;
+        self.container.is_none_or(|x| mime.format() == x)
+            && self.vcodec.is_none_or(|x| mime.vcodec() == Some(x))
+            && self.acodec.is_none_or(|x| mime.acodec() == Some(x))
+            && self.max_height.is_none_or(|x| {
+                mime.vcodec().is_none() || format.height.is_some_and(|height| height <= x)
+            })
+            && self
+                .min_audio_bitrate
+                .is_none_or(|x| mime.acodec().is_some() && format.bitrate >= x)
+            && !(self.exclude_drc && format.is_drc == Some(true))
+            && self
+                .hdr
+                .is_none_or(|x| mime.vcodec().is_some() && format.is_hdr() == x)
+    }
+
+    /// Returns an iterator over all formats of the video satisfying the constraints.
+    pub fn all<'a>(&'a self, video: &'a Video) -> impl Iterator<Item = &'a VideoFormat> {
+        video.all_formats().filter(|format| self.matches(format))
+    }
+
+    /// Finds the best format satisfying the constraints, in general prefer:
+    /// known codecs > video quality > audio quality > codec order > bitrate > extension > no DRC.
+    #[must_use]
+    pub fn best<'a>(&self, video: &'a Video) -> Option<&'a VideoFormat> {
+        video
+            .all_formats()
+            .filter(|format| self.matches(format))
+            .max_by(|a, b| {
+                let known = compare_known(a, b);
+                if known.is_ne() {
+                    return known;
+                } else if a.quality != b.quality {
+                    return a.quality.cmp(&b.quality);
+                } else if a.audio_quality != b.audio_quality {
+                    return a.audio_quality.cmp(&b.audio_quality);
+                }
+                let codecs = self.compare_codecs(a, b);
+                if codecs.is_ne() {
+                    return codecs;
+                } else if a.bitrate != b.bitrate {
+                    return a.bitrate.cmp(&b.bitrate);
+                } else if a.mime_type.format() != b.mime_type.format() {
+                    return a.mime_type.format().cmp(&b.mime_type.format());
+                }
+                compare_drc(a, b, self.prefer_drc)
+            })
+    }
+
+    /// Compares two formats by the codec orders, equal if no order is set.
+    fn compare_codecs(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let vcodec = self.vcodec_order.as_ref().map_or(Ordering::Equal, |order| {
+            rank(order, a.mime_type.vcodec()).cmp(&rank(order, b.mime_type.vcodec()))
+        });
+        let acodec = self.acodec_order.as_ref().map_or(Ordering::Equal, |order| {
+            rank(order, a.mime_type.acodec()).cmp(&rank(order, b.mime_type.acodec()))
+        });
+        vcodec.then(acodec)
+    }
+}
+
+/// Preferences used for ranking formats in [`Video::best_audio_with()`] and
+/// [`Video::best_video_with()`].
+///
+/// Unlike [`FormatSelector`], preferences only change the order in which formats are ranked,
+/// except for [`Self::max_quality()`] which is a hard limit. The default preferences rank formats
+/// the same as [`crate::structs::compare_audio_default()`] and
+/// [`crate::structs::compare_video_default()`].
+///
+/// ```no_run
+/// # use yinfo::{select::FormatPreferences, Acodec, Format, Quality, Video};
+/// # fn run(video: &Video) {
+/// let preferences = FormatPreferences::new()
+///     .acodec_order(vec![Acodec::AAC, Acodec::Opus])
+///     .container(Format::MP4)
+///     .max_quality(Quality::HD1080);
+/// let audio = video.best_audio_with(&preferences);
+/// let video = video.best_video_with(&preferences);
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FormatPreferences {
+    acodec_order: Option<Vec<Acodec>>,
+    vcodec_order: Option<Vec<Vcodec>>,
+    container: Option<Format>,
+    max_quality: Option<Quality>,
+    prefer_drc: bool,
+    prefer_hdr: Option<bool>,
+}
+
+impl FormatPreferences {
+    /// Creates the default preferences.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ranks audio codecs in the given order, the first being the most preferred. Codecs not in
+    /// the list are ranked last.
+    ///
+    /// By default codecs are ranked by the order of [`Acodec`], which favours open codecs such as
+    /// Opus. Players which lack them, such as Apple's, may want `[Acodec::AAC, Acodec::MP4A]`.
+    #[must_use]
+    pub fn acodec_order(mut self, order: Vec<Acodec>) -> Self {
+        self.acodec_order = Some(order);
+        self
+    }
+
+    /// Ranks video codecs in the given order, the first being the most preferred. Codecs not in
+    /// the list are ranked last.
+    ///
+    /// By default codecs are ranked by the order of [`Vcodec`].
+    #[must_use]
+    pub fn vcodec_order(mut self, order: Vec<Vcodec>) -> Self {
+        self.vcodec_order = Some(order);
+        self
+    }
+
+    /// Prefers the given container over others.
+    #[must_use]
+    pub fn container(mut self, container: Format) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Never select formats above the given video quality.
+    #[must_use]
+    pub fn max_quality(mut self, quality: Quality) -> Self {
+        self.max_quality = Some(quality);
+        self
+    }
+
+    /// Prefers formats with dynamic range compression over otherwise identical formats without,
+    /// which may be better for voice content. By default formats without are preferred.
+    #[must_use]
+    pub fn prefer_drc(mut self, prefer: bool) -> Self {
+        self.prefer_drc = prefer;
+        self
+    }
+
+    /// Prefers HDR formats if `true`, otherwise SDR formats, over formats of the same quality
+    /// regardless of codec and bitrate. See [`VideoFormat::is_hdr()`].
+    ///
+    /// By default SDR formats only win otherwise identical formats.
+    #[must_use]
+    pub fn prefer_hdr(mut self, prefer: bool) -> Self {
+        self.prefer_hdr = Some(prefer);
+        self
+    }
+
+    /// Returns whether the format is allowed by the hard limits of these preferences.
+    #[must_use]
+    pub fn allows(&self, format: &VideoFormat) -> bool {
+        self.max_quality.is_none_or(|x| format.quality <= x)
+    }
+
+    /// Compares two formats for audio, in general prefer:
+    /// known codecs > audio quality > acodec > bitrate > extension > no DRC.
+    #[must_use]
+    pub fn compare_audio(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let known = compare_known(a, b);
+        if known.is_ne() {
+            return known;
+        }
+        if a.audio_quality != b.audio_quality {
+            return a.audio_quality.cmp(&b.audio_quality);
+        }
+        let acodec = match &self.acodec_order {
+            Some(order) => {
+                rank(order, a.mime_type.acodec()).cmp(&rank(order, b.mime_type.acodec()))
+            }
+            None => a.mime_type.acodec().cmp(&b.mime_type.acodec()),
+        };
+        acodec
+            .then_with(|| a.bitrate.cmp(&b.bitrate))
+            .then_with(|| self.compare_container(a, b))
+            .then_with(|| compare_drc(a, b, self.prefer_drc))
+    }
+
+    /// Compares two formats for video, in general prefer:
+    /// known codecs > video quality > fps > HDR preference > vcodec > bitrate > extension > SDR.
+    #[must_use]
+    pub fn compare_video(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let known = compare_known(a, b);
+        if known.is_ne() {
+            return known;
+        }
+        if a.quality != b.quality {
+            return a.quality.cmp(&b.quality);
+        }
+        // HDR streams look washed out in players without tonemapping, so SDR wins ties
+        let sdr = (!a.is_hdr()).cmp(&!b.is_hdr());
+        let hdr = match self.prefer_hdr {
+            Some(true) => sdr.reverse(),
+            Some(false) => sdr,
+            None => Ordering::Equal,
+        };
+        let vcodec = match &self.vcodec_order {
+            Some(order) => {
+                rank(order, a.mime_type.vcodec()).cmp(&rank(order, b.mime_type.vcodec()))
+            }
+            None => a.mime_type.vcodec().cmp(&b.mime_type.vcodec()),
+        };
+        a.fps
+            .cmp(&b.fps)
+            .then(hdr)
+            .then(vcodec)
+            .then_with(|| a.bitrate.cmp(&b.bitrate))
+            .then_with(|| self.compare_container(a, b))
+            .then(sdr)
+    }
+
+    fn compare_container(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let (a, b) = (a.mime_type.format(), b.mime_type.format());
+        match self.container {
+            Some(container) => (a == container).cmp(&(b == container)),
+            None => a.cmp(&b),
+        }
+    }
+}
+
+/// Compares two formats by whether their codecs are known, formats with unknown codecs may not
+/// be playable so they are ranked below all others.
+fn compare_known(a: &VideoFormat, b: &VideoFormat) -> Ordering {
+    (!a.mime_type.has_unknown_codec()).cmp(&!b.mime_type.has_unknown_codec())
+}
+
+/// Compares two formats by dynamic range compression, DRC formats are duplicates of regular
+/// formats so this is only useful as the last tiebreak.
+fn compare_drc(a: &VideoFormat, b: &VideoFormat, prefer_drc: bool) -> Ordering {
+    let (a, b) = (a.is_drc == Some(true), b.is_drc == Some(true));
+    if prefer_drc {
+        a.cmp(&b)
+    } else {
+        b.cmp(&a)
+    }
+}
+
+/// Rank of the value in the preference order, higher is better and `None` is the worst.
+fn rank<T: PartialEq>(order: &[T], value: Option<T>) -> Option<usize> {
+    let value = value?;
+    order
+        .iter()
+        .position(|x| *x == value)
+        .map(|pos| order.len() - pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video() -> Video {
+        serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap()
+    }
+
+    #[test]
+    fn test_select_video() {
+        let video = video();
+        let selector = FormatSelector::new()
+            .container(Format::Webm)
+            .vcodec(Vcodec::VP9)
+            .max_height(720);
+        assert_eq!(selector.best(&video).map(|x| x.itag), Some(247));
+        assert_eq!(selector.all(&video).count(), 1);
+    }
+
+    #[test]
+    fn test_select_audio() {
+        let video = video();
+        let selector = FormatSelector::new()
+            .acodec(Acodec::Opus)
+            .min_audio_bitrate(96_000)
+            .exclude_drc();
+        let formats: Vec<&VideoFormat> = selector.all(&video).collect();
+        assert_eq!(formats.len(), 1);
+        assert_eq!(formats[0].itag, 251);
+        assert_ne!(formats[0].is_drc, Some(true));
+    }
+
+    #[test]
+    fn test_select_hdr() {
+        let video = video();
+        assert!(FormatSelector::new().hdr(true).best(&video).is_none());
+        assert_eq!(
+            FormatSelector::new()
+                .hdr(false)
+                .best(&video)
+                .map(|x| x.itag),
+            Some(137)
+        );
+    }
+
+    #[test]
+    fn test_preferences_default() {
+        let video = video();
+        let preferences = FormatPreferences::default();
+        assert_eq!(
+            video.best_audio_with(&preferences).map(|x| x.itag),
+            video.best_audio().map(|x| x.itag)
+        );
+        assert_eq!(
+            video.best_video_with(&preferences).map(|x| x.itag),
+            video.best_video().map(|x| x.itag)
+        );
+    }
+
+    #[test]
+    fn test_preferences() {
+        let video = video();
+        let preferences = FormatPreferences::new()
+            .acodec_order(vec![Acodec::AAC, Acodec::Opus])
+            .vcodec_order(vec![Vcodec::AVC, Vcodec::VP9])
+            .max_quality(Quality::HD720);
+        assert_eq!(
+            video.best_audio_with(&preferences).map(|x| x.itag),
+            Some(140)
+        );
+        assert_eq!(
+            video.best_video_with(&preferences).map(|x| x.itag),
+            Some(136)
+        );
+    }
+
+    #[test]
+    fn test_codec_order() {
+        let video = video();
+        assert_eq!(video.best_audio().map(|x| x.itag), Some(251));
+        let preferences = FormatPreferences::new().acodec_order(vec![Acodec::AAC]);
+        assert_eq!(
+            video.best_audio_with(&preferences).map(|x| x.itag),
+            Some(140)
+        );
+
+        // without an order the higher bitrate wins
+        let selector = FormatSelector::new().max_height(720);
+        assert_eq!(selector.best(&video).map(|x| x.itag), Some(247));
+        let selector = selector.vcodec_order(vec![Vcodec::AVC, Vcodec::VP9]);
+        assert_eq!(selector.best(&video).map(|x| x.itag), Some(136));
+    }
+
+    #[test]
+    fn test_drc_tiebreak() {
+        let video = video();
+        let mut formats = video.all_formats().filter(|x| x.itag == 251);
+        let (regular, drc) = (formats.next().unwrap(), formats.next().unwrap());
+        assert_eq!(drc.is_drc, Some(true));
+
+        let preferences = FormatPreferences::new();
+        assert_eq!(preferences.compare_audio(regular, drc), Ordering::Greater);
+        assert_eq!(preferences.compare_audio(drc, regular), Ordering::Less);
+        let preferences = preferences.prefer_drc(true);
+        assert_eq!(preferences.compare_audio(regular, drc), Ordering::Less);
+
+        let selector = FormatSelector::new().acodec(Acodec::Opus);
+        assert_eq!(selector.best(&video).unwrap().is_drc, None);
+        let selector = selector.prefer_drc(true);
+        assert_eq!(selector.best(&video).unwrap().is_drc, Some(true));
+    }
+
+    #[test]
+    fn test_prefer_hdr() {
+        let mut video = video();
+        // turn the VP9 1080p format into HDR
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter_mut()
+            .find(|x| x.itag == 248)
+            .unwrap();
+        let color_info = format.color_info.as_mut().unwrap();
+        color_info.primaries = Some("COLOR_PRIMARIES_BT2020".to_owned());
+        color_info.transfer_characteristics =
+            Some("COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084".to_owned());
+        assert!(format.is_hdr());
+
+        let preferences = FormatPreferences::new().vcodec_order(vec![Vcodec::VP9, Vcodec::AVC]);
+        assert_eq!(
+            video.best_video_with(&preferences).map(|x| x.itag),
+            Some(248)
+        );
+        let preferences = preferences.prefer_hdr(false);
+        assert_eq!(
+            video.best_video_with(&preferences).map(|x| x.itag),
+            Some(137)
+        );
+        let preferences = FormatPreferences::new().prefer_hdr(true);
+        assert_eq!(
+            video.best_video_with(&preferences).map(|x| x.itag),
+            Some(248)
+        );
+    }
+
+    #[test]
+    fn test_fps_tiebreak() {
+        let mut video = video();
+        assert_eq!(video.best_video().map(|x| x.itag), Some(248));
+
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter_mut()
+            .find(|x| x.itag == 137)
+            .unwrap();
+        format.fps = Some(50);
+        format.quality_label = Some("1080p50".to_owned());
+        assert_eq!(video.best_video().map(|x| x.itag), Some(137));
+    }
+
+    #[test]
+    fn test_select_unsatisfiable() {
+        let video = video();
+        let selector = FormatSelector::new()
+            .container(Format::MP4)
+            .vcodec(Vcodec::VP9);
+        assert!(selector.best(&video).is_none());
+        assert_eq!(selector.all(&video).count(), 0);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+/// A level of the storyboard of a video, the thumbnails shown when seeking. Returned by
+/// [`crate::structs::Video::storyboards()`].
+///
+/// Thumbnails are packed into sprite sheets of [`Self::columns`] by [`Self::rows`] thumbnails,
+/// use [`Self::sheet_urls()`] to get the url of every sheet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoryboardLevel {
+    /// Level of detail, higher levels have larger thumbnails.
+    pub level: u32,
+    /// Url of the sheets, with `$M` in place of the sheet index.
+    pub url_template: String,
+    pub thumbnail_width: u32,
+    pub thumbnail_height: u32,
+    /// Total number of thumbnails across all sheets.
+    pub thumbnail_count: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Time between thumbnails in milliseconds. This is 0 if the thumbnails are spread evenly
+    /// over the video instead, which is usually the case for the first level.
+    pub interval_ms: u32,
+}
+
+impl StoryboardLevel {
+    /// Returns the number of sprite sheets of this level.
+    #[must_use]
+    pub fn sheet_count(&self) -> u32 {
+        self.thumbnail_count.div_ceil(self.columns * self.rows)
+    }
+
+    /// Returns the url of the sheet with the given index.
+    #[must_use]
+    pub fn sheet_url(&self, index: u32) -> String {
+        self.url_template.replace("$M", &index.to_string())
+    }
+
+    /// Returns the urls of all sheets of this level, in order.
+    #[must_use]
+    pub fn sheet_urls(&self) -> Vec<String> {
+        (0..self.sheet_count()).map(|x| self.sheet_url(x)).collect()
+    }
+}
+
+/// Parses a storyboard spec, levels which are malformed are skipped.
+///
+/// The spec is the url template followed by a `|` separated list of levels. Each level is given
+/// as `width#height#count#columns#rows#interval#name#signature`, the name replaces `$N` and the
+/// index of the level replaces `$L` in the url template.
+pub(crate) fn parse_spec(spec: &str) -> Vec<StoryboardLevel> {
+    let mut parts = spec.split('|');
+    let Some(template) = parts.next() else {
+        return Vec::new();
+    };
+
+    parts
+        .zip(0..)
+        .filter_map(|(level, index)| parse_level(template, level, index))
+        .collect()
+}
+
+fn parse_level(template: &str, level: &str, index: u32) -> Option<StoryboardLevel> {
+    let args: Vec<&str> = level.split('#').collect();
+    let [width, height, count, columns, rows, interval, name, sigh] = args[..] else {
+        return None;
+    };
+    let [width, height, count, columns, rows, interval] =
+        [width, height, count, columns, rows, interval].map(|x| x.parse::<u32>().ok());
+
+    let url = template
+        .replace("$L", &index.to_string())
+        .replace("$N", name);
+    let level = StoryboardLevel {
+        level: index,
+        url_template: format!("{url}&sigh={sigh}"),
+        thumbnail_width: width?,
+        thumbnail_height: height?,
+        thumbnail_count: count?,
+        columns: columns?,
+        rows: rows?,
+        interval_ms: interval?,
+    };
+    (level.columns > 0 && level.rows > 0).then_some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = "https://i.ytimg.com/sb/dQw4w9WgXcQ/storyboard3_L$L/$N.jpg?sqp=-oaymwENSDfyq4qpAwVwAcABBqLzl_8DBgiYlbKsBg==|48#27#100#10#10#0#default#rs$AOn4CLBmF1kB1X5Vd7wfBr7pzdAFc3Ohsw|80#45#127#10#10#2000#M$M#rs$AOn4CLDo6aWK8nEDDqUPfQyJqWJEsaMe5g|160#90#127#5#5#2000#M$M#rs$AOn4CLAj8h5tFqBZQVfyAtApN6dMs4Fnsg";
+
+    #[test]
+    fn test_parse_spec() {
+        let levels = parse_spec(SPEC);
+        assert_eq!(levels.len(), 3);
+
+        let level = &levels[0];
+        assert_eq!((level.thumbnail_width, level.thumbnail_height), (48, 27));
+        assert_eq!(level.interval_ms, 0);
+        assert_eq!(level.sheet_count(), 1);
+        assert_eq!(
+            level.sheet_url(0),
+            "https://i.ytimg.com/sb/dQw4w9WgXcQ/storyboard3_L0/default.jpg?sqp=-oaymwENSDfyq4qpAwVwAcABBqLzl_8DBgiYlbKsBg==&sigh=rs$AOn4CLBmF1kB1X5Vd7wfBr7pzdAFc3Ohsw"
+        );
+
+        let level = &levels[2];
+        assert_eq!(level.level, 2);
+        assert_eq!((level.columns, level.rows), (5, 5));
+        assert_eq!(level.interval_ms, 2000);
+        assert_eq!(level.sheet_count(), 6);
+        let urls = level.sheet_urls();
+        assert_eq!(urls.len(), 6);
+        assert!(
+            urls[5].starts_with("https://i.ytimg.com/sb/dQw4w9WgXcQ/storyboard3_L2/M5.jpg?sqp=")
+        );
+    }
+
+    #[test]
+    fn test_parse_spec_bad() {
+        assert!(parse_spec("").is_empty());
+        let levels = parse_spec("https://i.ytimg.com/sb/$L/$N.jpg?a=b|48#27#abc#10#10#0#default#x|48#27#100#0#10#0#default#x|48#27#100#10#10#0#default");
+        assert!(levels.is_empty());
+    }
+}
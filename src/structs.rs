@@ -1,56 +1,542 @@
-use std::cmp::Ordering::Equal;
+use std::{
+    cmp::Ordering::{self, Equal},
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use url::Url;
 
-use crate::mime::Mime;
+use crate::{
+    errors::Error,
+    innertube::get_video_id,
+    mime::{Av1Profile, AvcProfile, Mime},
+    query::Text,
+    select::FormatPreferences,
+    storyboard::{parse_spec, StoryboardLevel},
+    utils::between,
+};
 
 /// Main structure for video details returned by `info()`.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Videos compare equal if their responses are equal, regardless of when they were fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Video {
     pub response_context: ResponseContext,
     pub playability_status: PlayabilityStatus,
     pub video_details: VideoDetails,
     pub streaming_data: StreamData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_config: Option<PlayerConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captions: Option<Captions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub microformat: Option<Microformat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storyboards: Option<Storyboards>,
+
+    /// Fields of the response which are not modelled by this crate.
+    ///
+    /// This is meant for prototyping with fields YouTube has added recently, please open an
+    /// issue for fields which are used repeatedly so they can be added properly.
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+
+    /// When the response was received, used to resolve [`StreamData::expires_in_seconds`].
+    #[serde(skip)]
+    pub(crate) fetched_at: Option<SystemTime>,
+}
+
+impl PartialEq for Video {
+    fn eq(&self, other: &Self) -> bool {
+        // Destructured so new fields are not missed
+        let Video {
+            response_context,
+            playability_status,
+            video_details,
+            streaming_data,
+            player_config,
+            captions,
+            microformat,
+            storyboards,
+            extras,
+            fetched_at: _,
+        } = self;
+        *response_context == other.response_context
+            && *playability_status == other.playability_status
+            && *video_details == other.video_details
+            && *streaming_data == other.streaming_data
+            && *player_config == other.player_config
+            && *captions == other.captions
+            && *microformat == other.microformat
+            && *storyboards == other.storyboards
+            && *extras == other.extras
+    }
 }
 
 impl Video {
-    /// Finds the best audio format for the given video, in general prefer:
-    /// audio quality > acodec > bitrate > extension.
+    /// Returns the id of the video.
     #[must_use]
-    pub fn best_audio(&self) -> Option<&VideoFormat> {
-        self.all_formats().max_by(|a, b| {
-            if a.audio_quality != b.audio_quality {
-                return a.audio_quality.cmp(&b.audio_quality);
-            } else if a.mime_type.acodec() != b.mime_type.acodec() {
-                return a.mime_type.acodec().cmp(&b.mime_type.acodec());
-            } else if a.bitrate != b.bitrate {
-                return a.bitrate.cmp(&b.bitrate);
-            } else if a.mime_type.format() != b.mime_type.format() {
-                return a.mime_type.format().cmp(&b.mime_type.format());
-            }
-            Equal
+    pub fn id(&self) -> &str {
+        &self.video_details.video_id
+    }
+
+    /// Returns the title of the video.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.video_details.title
+    }
+
+    /// Returns the name of the channel which uploaded the video.
+    #[must_use]
+    pub fn author(&self) -> &str {
+        &self.video_details.author
+    }
+
+    /// Returns the url of the largest thumbnail of the video.
+    #[must_use]
+    pub fn thumbnail_url(&self) -> Option<&str> {
+        self.video_details
+            .thumbnails
+            .thumbnails
+            .iter()
+            .max_by_key(|x| x.width * x.height)
+            .map(|x| x.url.as_str())
+    }
+
+    /// Returns the length of the video.
+    ///
+    /// Some videos, such as past live streams, report a length of zero, in which case the longest
+    /// [`VideoFormat::approx_duration()`] is used instead. `None` for ongoing live streams.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        match self.video_details.length_seconds {
+            0 => self
+                .all_formats()
+                .filter_map(VideoFormat::approx_duration)
+                .max(),
+            x => Some(Duration::from_secs(x)),
+        }
+    }
+
+    /// Returns whether the response reported the video as age restricted, see
+    /// [`PlayabilityStatus::is_age_restricted()`].
+    #[must_use]
+    pub fn is_age_restricted(&self) -> bool {
+        self.playability_status.is_age_restricted()
+    }
+
+    /// Returns the caption tracks of the video, empty if the video has no captions.
+    ///
+    /// Use `Innertube::transcript()` to fetch the contents of a track.
+    #[must_use]
+    pub fn captions(&self) -> &[CaptionTrack] {
+        self.captions.as_ref().map_or(&[], |x| {
+            &x.player_captions_tracklist_renderer.caption_tracks
         })
     }
 
-    /// Finds the best video format for the given video, in general prefer:
-    /// video quality > acodec > bitrate > extension.
+    /// Returns the microformat of the video, which is only included by some clients.
     #[must_use]
-    pub fn best_video(&self) -> Option<&VideoFormat> {
-        self.all_formats().max_by(|a, b| {
-            if a.quality != b.quality {
-                return a.quality.cmp(&b.quality);
-            } else if a.mime_type.vcodec() != b.mime_type.vcodec() {
-                return a.mime_type.vcodec().cmp(&b.mime_type.vcodec());
-            } else if a.bitrate != b.bitrate {
-                return a.bitrate.cmp(&b.bitrate);
-            } else if a.mime_type.format() != b.mime_type.format() {
-                return a.mime_type.format().cmp(&b.mime_type.format());
+    pub fn microformat(&self) -> Option<&PlayerMicroformat> {
+        self.microformat
+            .as_ref()
+            .map(|x| &x.player_microformat_renderer)
+    }
+
+    /// Returns the url of the channel which uploaded the video.
+    #[must_use]
+    pub fn channel_url(&self) -> String {
+        format!(
+            "https://www.youtube.com/channel/{}",
+            self.video_details.channel_id
+        )
+    }
+
+    /// Returns the date the video was published, see [`Self::microformat()`].
+    #[must_use]
+    pub fn publish_date(&self) -> Option<Date> {
+        Date::parse(self.microformat()?.publish_date.as_deref()?)
+    }
+
+    /// Returns the date the video was uploaded, see [`Self::microformat()`].
+    #[must_use]
+    pub fn upload_date(&self) -> Option<Date> {
+        Date::parse(self.microformat()?.upload_date.as_deref()?)
+    }
+
+    /// Returns the category of the video, such as `"Music"`, see [`Self::microformat()`].
+    #[must_use]
+    pub fn category(&self) -> Option<&str> {
+        self.microformat()?.category.as_deref()
+    }
+
+    /// Returns the countries the video is available in, see [`Self::microformat()`]. Empty if
+    /// there is no microformat.
+    #[must_use]
+    pub fn available_countries(&self) -> &[String] {
+        self.microformat()
+            .map_or(&[], |x| x.available_countries.as_slice())
+    }
+
+    /// Returns whether the video is available in the given country, such as `"US"`. `None` if
+    /// this is not known.
+    #[must_use]
+    pub fn available_in(&self, country: &str) -> Option<bool> {
+        let countries = self.available_countries();
+        if countries.is_empty() {
+            return None;
+        }
+        Some(countries.iter().any(|x| x.eq_ignore_ascii_case(country)))
+    }
+
+    /// Returns the storyboard levels of the video, ordered from smallest to largest thumbnails.
+    /// Empty for live streams or if the video has no storyboard.
+    #[must_use]
+    pub fn storyboards(&self) -> Vec<StoryboardLevel> {
+        self.storyboards
+            .as_ref()
+            .and_then(|x| x.player_storyboard_spec_renderer.as_ref())
+            .map(|x| parse_spec(&x.spec))
+            .unwrap_or_default()
+    }
+
+    /// Returns the live details of the video, only present for live streams and past live
+    /// streams.
+    #[must_use]
+    pub fn live_broadcast_details(&self) -> Option<&LiveBroadcastDetails> {
+        self.microformat()?.live_broadcast_details.as_ref()
+    }
+
+    /// Returns when an upcoming live stream or premiere is scheduled to start, see
+    /// [`PlayabilityStatus::scheduled_start_time()`].
+    #[must_use]
+    pub fn scheduled_start(&self) -> Option<SystemTime> {
+        self.playability_status.scheduled_start_time()
+    }
+
+    /// Returns whether the video is live, upcoming or a past live stream, see [`LiveStatus`].
+    #[must_use]
+    pub fn live_status(&self) -> LiveStatus {
+        let details = &self.video_details;
+        let broadcast = self.live_broadcast_details();
+        if details.is_upcoming == Some(true)
+            || self.playability_status.status == PlayabilityState::LiveStreamOffline
+        {
+            LiveStatus::Upcoming
+        } else if details.is_live == Some(true) || broadcast.is_some_and(|x| x.is_live_now) {
+            LiveStatus::Live
+        } else if details.is_post_live_dvr == Some(true) {
+            LiveStatus::PostLiveProcessing
+        } else if details.is_live_content || broadcast.is_some() {
+            LiveStatus::WasLive
+        } else {
+            LiveStatus::NotLive
+        }
+    }
+
+    /// Returns the loudness of the video in decibels, relative to YouTube's target loudness.
+    ///
+    /// Players normalize the volume using this, a positive value means the video is louder than
+    /// the target and should be turned down.
+    #[must_use]
+    pub fn loudness_db(&self) -> Option<f64> {
+        self.player_config
+            .as_ref()
+            .and_then(|x| x.audio_config.as_ref())
+            .and_then(|x| x.loudness_db)
+    }
+
+    /// Returns the audio-only formats grouped by the display name of their audio track, in the
+    /// order the tracks first appear. See [`VideoFormat::track_name()`].
+    ///
+    /// Empty for videos with a single audio track, since their formats have no track.
+    #[must_use]
+    pub fn audio_tracks(&self) -> Vec<(&str, Vec<&VideoFormat>)> {
+        let mut tracks: Vec<(&str, Vec<&VideoFormat>)> = Vec::new();
+        for format in self.audio_only_formats() {
+            let Some(name) = format.track_name() else {
+                continue;
+            };
+            match tracks.iter_mut().find(|(x, _)| *x == name) {
+                Some((_, formats)) => formats.push(format),
+                None => tracks.push((name, vec![format])),
             }
-            Equal
+        }
+        tracks
+    }
+
+    /// Finds the best audio format of the audio track with the given id, such as `"en.4"`. See
+    /// [`VideoFormat::track_id()`].
+    #[must_use]
+    pub fn best_audio_for_track(&self, track_id: &str) -> Option<&VideoFormat> {
+        let preferences = FormatPreferences::default();
+        self.all_formats()
+            .filter(|format| format.track_id() == Some(track_id))
+            .max_by(|a, b| preferences.compare_audio(a, b))
+    }
+
+    /// Returns the linear gain to multiply samples with to normalize the volume, like YouTube's
+    /// player does, see [`Self::loudness_db()`].
+    ///
+    /// Loud videos are turned down but quiet videos are never turned up, so the gain is at most
+    /// `1.0`.
+    #[must_use]
+    pub fn normalization_gain(&self) -> Option<f64> {
+        self.loudness_db().map(normalization_gain)
+    }
+
+    /// Returns when the format urls of the video expire, after which the video has to be fetched
+    /// again, see [`crate::innertube::Innertube::refresh()`].
+    ///
+    /// This is taken from the urls if possible, otherwise from the relative expiry of the
+    /// response, which is only known for videos returned by
+    /// [`crate::innertube::Innertube::info()`].
+    #[must_use]
+    pub fn formats_expire_at(&self) -> Option<SystemTime> {
+        self.streaming_data
+            .expires_at()
+            .or_else(|| Some(self.fetched_at? + self.streaming_data.expires_in()?))
+    }
+
+    /// Finds the best audio format for the given video, see [`compare_audio_default()`] for the
+    /// ordering used.
+    #[must_use]
+    pub fn best_audio(&self) -> Option<&VideoFormat> {
+        self.best_audio_with(&FormatPreferences::default())
+    }
+
+    /// Finds the best audio format for the given video, ranked according to the preferences.
+    ///
+    /// Formats of the default audio track are always preferred over other tracks, such as dubs.
+    #[must_use]
+    pub fn best_audio_with(&self, preferences: &FormatPreferences) -> Option<&VideoFormat> {
+        self.all_formats()
+            .filter(|format| preferences.allows(format))
+            .max_by(|a, b| {
+                a.is_default_audio_track()
+                    .cmp(&b.is_default_audio_track())
+                    .then_with(|| preferences.compare_audio(a, b))
+            })
+    }
+
+    /// Finds the best audio format of the audio track in the given language, such as `"en"` or
+    /// `"es"`. See [`AudioTrack::language()`].
+    ///
+    /// Returns `None` if the video has no audio track in the language, which is always the case
+    /// for videos with a single audio track.
+    #[must_use]
+    pub fn best_audio_for_language(&self, lang: &str) -> Option<&VideoFormat> {
+        let preferences = FormatPreferences::default();
+        self.all_formats()
+            .filter(|format| {
+                format
+                    .audio_track
+                    .as_ref()
+                    .is_some_and(|track| track.is_language(lang))
+            })
+            .max_by(|a, b| preferences.compare_audio(a, b))
+    }
+
+    /// Finds the best audio format for the given video using the given comparison, the greatest
+    /// format is considered the best.
+    #[must_use]
+    pub fn best_audio_by(
+        &self,
+        cmp: impl Fn(&VideoFormat, &VideoFormat) -> Ordering,
+    ) -> Option<&VideoFormat> {
+        self.all_formats().max_by(|a, b| cmp(a, b))
+    }
+
+    /// Finds the best video format for the given video, see [`compare_video_default()`] for the
+    /// ordering used.
+    #[must_use]
+    pub fn best_video(&self) -> Option<&VideoFormat> {
+        self.best_video_with(&FormatPreferences::default())
+    }
+
+    /// Finds the best video format for the given video, ranked according to the preferences.
+    #[must_use]
+    pub fn best_video_with(&self, preferences: &FormatPreferences) -> Option<&VideoFormat> {
+        self.all_formats()
+            .filter(|format| preferences.allows(format))
+            .max_by(|a, b| preferences.compare_video(a, b))
+    }
+
+    /// Finds the best video format for the given video using the given comparison, the greatest
+    /// format is considered the best.
+    #[must_use]
+    pub fn best_video_by(
+        &self,
+        cmp: impl Fn(&VideoFormat, &VideoFormat) -> Ordering,
+    ) -> Option<&VideoFormat> {
+        self.all_formats().max_by(|a, b| cmp(a, b))
+    }
+
+    /// Finds the worst audio format for the given video, the opposite of [`Self::best_audio()`].
+    ///
+    /// Only formats with a stream url are considered. Ultralow quality formats are skipped unless
+    /// there is nothing else.
+    #[must_use]
+    pub fn worst_audio(&self) -> Option<&VideoFormat> {
+        self.small_audio_formats()
+            .min_by(|a, b| compare_audio_default(a, b))
+    }
+
+    /// Finds the worst video format for the given video, the opposite of [`Self::best_video()`].
+    ///
+    /// Only formats with a stream url are considered.
+    #[must_use]
+    pub fn worst_video(&self) -> Option<&VideoFormat> {
+        self.video_formats()
+            .filter(|format| format.has_stream_url())
+            .min_by(|a, b| compare_video_default(a, b))
+    }
+
+    /// Finds the audio format with the smallest estimated size, using the content length if
+    /// present, otherwise the bitrate and duration.
+    ///
+    /// Only formats with a stream url are considered. Ultralow quality formats are skipped unless
+    /// there is nothing else.
+    #[must_use]
+    pub fn smallest_audio(&self) -> Option<&VideoFormat> {
+        self.small_audio_formats()
+            .filter_map(|format| Some((format.size_estimate()?, format)))
+            .min_by_key(|(size, _)| *size)
+            .map(|(_, format)| format)
+    }
+
+    /// Audio formats with a stream url, without ultralow quality formats if there are others.
+    fn small_audio_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        let has_other = self.audio_formats().any(|format| {
+            format.has_stream_url() && format.audio_quality != Some(AudioQuality::Ultralow)
+        });
+        self.audio_formats().filter(move |format| {
+            format.has_stream_url()
+                && !(has_other && format.audio_quality == Some(AudioQuality::Ultralow))
         })
     }
 
+    /// Finds the best format containing both audio and video, in general prefer:
+    /// video quality > bitrate > extension.
+    ///
+    /// These are usually only available at lower qualities, returns `None` if there are no such
+    /// formats, in which case the best audio and video formats should be used instead.
+    #[must_use]
+    pub fn best_muxed(&self) -> Option<&VideoFormat> {
+        self.all_formats()
+            .filter(|format| format.is_progressive())
+            .max_by(|a, b| {
+                if a.quality != b.quality {
+                    return a.quality.cmp(&b.quality);
+                } else if a.bitrate != b.bitrate {
+                    return a.bitrate.cmp(&b.bitrate);
+                } else if a.mime_type.format() != b.mime_type.format() {
+                    return a.mime_type.format().cmp(&b.mime_type.format());
+                }
+                Equal
+            })
+    }
+
+    /// Finds the format with the given itag.
+    #[must_use]
+    pub fn format_by_itag(&self, itag: u32) -> Option<&VideoFormat> {
+        self.all_formats().find(|format| format.itag == itag)
+    }
+
+    /// Returns an iterator over all formats with an audio mime, these contain no video.
+    pub fn audio_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(|format| format.mime_type.is_audio())
+    }
+
+    /// Returns an iterator over all formats with a video mime, these may or may not contain audio.
+    pub fn video_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(|format| format.mime_type.is_video())
+    }
+
+    /// Returns an iterator over all formats containing both audio and video, see
+    /// [`VideoFormat::is_progressive()`].
+    pub fn progressive_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats().filter(|format| format.is_progressive())
+    }
+
+    /// Same as [`Self::audio_formats()`], since audio formats never contain video. Named to
+    /// match [`Self::video_only_formats()`].
+    pub fn audio_only_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.audio_formats()
+    }
+
+    /// Returns an iterator over all formats containing only video, see
+    /// [`VideoFormat::is_video_only()`].
+    pub fn video_only_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats().filter(|format| format.is_video_only())
+    }
+
+    /// Returns an iterator over all 360° formats, see [`VideoFormat::is_360()`].
+    pub fn formats_360(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats().filter(|format| format.is_360())
+    }
+
+    /// Returns an iterator over all video formats of exactly the given quality.
+    ///
+    /// Audio formats are skipped, as YouTube gives them all the lowest quality.
+    pub fn formats_for_quality(&self, quality: Quality) -> impl Iterator<Item = &VideoFormat> {
+        self.video_formats()
+            .filter(move |format| format.quality == quality)
+    }
+
+    /// Returns an iterator over all video formats of the given quality or better, see
+    /// [`Self::formats_for_quality()`].
+    pub fn formats_at_least_quality(&self, quality: Quality) -> impl Iterator<Item = &VideoFormat> {
+        self.video_formats()
+            .filter(move |format| format.quality >= quality)
+    }
+
+    /// Returns an iterator over all formats with audio of exactly the given quality.
+    pub fn formats_for_audio_quality(
+        &self,
+        quality: AudioQuality,
+    ) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(move |format| format.audio_quality == Some(quality))
+    }
+
+    /// Returns an iterator over all formats with audio of the given quality or better.
+    pub fn formats_at_least_audio_quality(
+        &self,
+        quality: AudioQuality,
+    ) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(move |format| matches!(format.audio_quality, Some(x) if x >= quality))
+    }
+
+    /// Returns whether any format has spatial or surround audio, see
+    /// [`VideoFormat::is_spatial()`].
+    #[must_use]
+    pub fn has_spatial_audio(&self) -> bool {
+        self.all_formats().any(VideoFormat::is_spatial)
+    }
+
+    /// Returns an iterator over all formats without dynamic range compression.
+    pub fn non_drc_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(|format| format.is_drc != Some(true))
+    }
+
+    /// Returns an iterator over all formats with dynamic range compression, these are duplicates
+    /// of regular audio formats.
+    pub fn drc_formats(&self) -> impl Iterator<Item = &VideoFormat> {
+        self.all_formats()
+            .filter(|format| format.is_drc == Some(true))
+    }
+
     /// Returns an iterator over all formats of the video. Useful if you want to do manual filtering
     /// or sorting over all formats.
     pub fn all_formats(&self) -> impl Iterator<Item = &VideoFormat> {
@@ -61,144 +547,2382 @@ impl Video {
     }
 }
 
+/// Default comparison used by [`Video::best_audio()`], in general prefer:
+/// known codecs > audio quality > acodec > bitrate > extension > no DRC.
+///
+/// DRC formats are otherwise identical duplicates with compressed dynamic range, which sounds
+/// worse for music, so they only win if nothing equivalent exists.
+///
+/// Use this as a building block for [`Video::best_audio_by()`] to only change a single tiebreak.
+#[must_use]
+pub fn compare_audio_default(a: &VideoFormat, b: &VideoFormat) -> Ordering {
+    FormatPreferences::default().compare_audio(a, b)
+}
+
+/// Default comparison used by [`Video::best_video()`], in general prefer:
+/// known codecs > video quality > fps > vcodec > bitrate > extension.
+///
+/// Use this as a building block for [`Video::best_video_by()`] to only change a single tiebreak.
+#[must_use]
+pub fn compare_video_default(a: &VideoFormat, b: &VideoFormat) -> Ordering {
+    FormatPreferences::default().compare_video(a, b)
+}
+
+/// Orders formats by video quality, then audio quality, then bitrate, with adaptive formats
+/// ordered above progressive ones. The itag, DRC and xtags are only used as final tiebreaks so
+/// distinct formats of a video never compare as equal.
+///
+/// This is a general ordering, for example for sorting formats with [`slice::sort_by()`]. It
+/// ignores codecs and containers, use [`Video::best_video()`] or [`Video::best_audio()`] to pick
+/// a format to download.
+#[must_use]
+pub fn compare_quality(a: &VideoFormat, b: &VideoFormat) -> Ordering {
+    a.quality
+        .cmp(&b.quality)
+        .then(a.audio_quality.cmp(&b.audio_quality))
+        .then(a.bitrate.cmp(&b.bitrate))
+        .then(b.is_progressive().cmp(&a.is_progressive()))
+        .then(a.itag.cmp(&b.itag))
+        .then(a.is_drc.cmp(&b.is_drc))
+        .then_with(|| a.xtags.cmp(&b.xtags))
+}
+
 /// Response context of the video, has no immediately useful information for most users.
 ///
 /// This includes information about the request or ongoing YouTube experiments.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ResponseContext {
     pub visitor_data: String,
     pub service_tracking_params: Vec<ServiceParams>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ServiceParams {
     pub service: String,
     pub params: Vec<ValuePair>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ValuePair {
     pub key: String,
     pub value: String,
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct VideoDetails {
+    /// Minimum age required to watch the video, rarely included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_limit: Option<u8>,
     pub allow_ratings: bool,
     pub author: String,
     pub channel_id: String,
+    /// Handle of the channel such as `"@RickAstleyYT"`, taken from the microformat if present.
+    /// Not part of the response, so it is not serialized.
+    #[serde(skip)]
+    pub channel_handle: Option<String>,
+    /// Avatar of the channel, which is not part of the player response and not serialized. See
+    /// [`crate::innertube::Innertube::channel_details()`].
+    #[serde(skip)]
+    pub channel_thumbnails: Option<Thumbnails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_chapter_map: Option<bool>,
     pub is_crawlable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_family_safe: Option<bool>,
+    /// Whether the video is currently live, only included for live streams.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_live: Option<bool>,
     pub is_live_content: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_live_dvr_enabled: Option<bool>,
     pub is_owner_viewing: bool,
+    /// Whether a live stream has just ended and is still being processed, during which only part
+    /// of the stream can be watched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_post_live_dvr: Option<bool>,
     pub is_private: bool,
     pub is_unplugged_corpus: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_upcoming: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub keywords: Option<Vec<String>>,
-    pub length_seconds: String,
+    /// Length of the video in seconds, 0 for live streams or if it could not be parsed.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_number",
+        serialize_with = "serialize_number"
+    )]
+    pub length_seconds: u64,
+    /// How many segments of a live stream players buffer ahead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_chunk_readahead: Option<u32>,
+    /// Description of the video, which may be truncated by YouTube for long descriptions.
     pub short_description: String,
     #[serde(rename = "thumbnail")]
     pub thumbnails: Thumbnails,
     pub title: String,
     pub video_id: String,
-    pub view_count: String,
+    /// View count of the video, 0 if it could not be parsed.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_number",
+        serialize_with = "serialize_number"
+    )]
+    pub view_count: u64,
+
+    /// Fields of the details which are not modelled by this crate, see [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl VideoDetails {
+    /// Returns the description without trailing whitespace, see [`Self::short_description`].
+    #[must_use]
+    pub fn description_text(&self) -> &str {
+        self.short_description.trim_end()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Thumbnails {
     pub thumbnails: Vec<Thumbnail>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Thumbnail {
     pub url: String,
     pub width: u32,
     pub height: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlayabilityStatus {
-    pub status: String,
+    pub status: PlayabilityState,
+    /// YouTube's explanation of why the video is not playable, such as
+    /// `"This video is private"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub playable_in_embed: Option<bool>,
+    /// Error shown by the player, which often has more detail than [`Self::reason`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_screen: Option<ErrorScreen>,
+    /// Included for upcoming live streams and premieres.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_streamability: Option<LiveStreamability>,
+    /// Set when the video is behind the age gate, regardless of the language of the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop_legacy_age_gate_reason: Option<u32>,
+}
+
+impl PlayabilityStatus {
+    /// Returns the reason of the status, falling back to the reason of the error screen.
+    #[must_use]
+    pub fn reason(&self) -> Option<String> {
+        self.reason
+            .clone()
+            .or_else(|| self.player_error_message()?.reason.clone())
+    }
+
+    /// Returns the additional explanation of the error screen, such as
+    /// `"The uploader has not made this video available in your country"`.
+    #[must_use]
+    pub fn subreason(&self) -> Option<String> {
+        self.player_error_message()?.subreason.clone()
+    }
+
+    /// Returns when an upcoming live stream or premiere is scheduled to start.
+    #[must_use]
+    pub fn scheduled_start_time(&self) -> Option<SystemTime> {
+        let secs = self
+            .live_streamability
+            .as_ref()?
+            .live_streamability_renderer
+            .offline_slate
+            .as_ref()?
+            .live_stream_offline_slate_renderer
+            .scheduled_start_time
+            .as_deref()?
+            .parse()
+            .ok()?;
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    fn player_error_message(&self) -> Option<&PlayerErrorMessage> {
+        self.error_screen
+            .as_ref()?
+            .player_error_message_renderer
+            .as_ref()
+    }
+
+    /// Returns whether the video requires the viewer to confirm their age.
+    ///
+    /// Depending on the client this is either reported as an age check, or as requiring a login
+    /// along with [`Self::desktop_legacy_age_gate_reason`]. Responses without that field are
+    /// recognized by their reason mentioning the age, which only works if the response is in
+    /// English. [`crate::innertube::Innertube`] sends player requests in English for this
+    /// reason, see [`crate::innertube::Config::hl`].
+    #[must_use]
+    pub fn is_age_restricted(&self) -> bool {
+        match &self.status {
+            PlayabilityState::AgeCheckRequired => true,
+            PlayabilityState::Other(status) => status == "AGE_VERIFICATION_REQUIRED",
+            PlayabilityState::LoginRequired | PlayabilityState::ContentCheckRequired => {
+                self.desktop_legacy_age_gate_reason.is_some()
+                    || self
+                        .reason
+                        .as_deref()
+                        .is_some_and(|x| x.contains("your age") || x.contains("inappropriate"))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the explanation of why the video is not available in the country of the
+    /// requesting IP, `None` if it is not restricted by region.
+    ///
+    /// YouTube does not mark region restrictions other than in the reason, so this only works if
+    /// the response is in English. [`crate::innertube::Innertube`] sends player requests in
+    /// English for this reason, see [`crate::innertube::Config::hl`].
+    ///
+    /// See [`Video::available_in()`] for the countries a playable video is available in.
+    #[must_use]
+    pub fn geo_restriction(&self) -> Option<String> {
+        if self.status == PlayabilityState::Ok {
+            return None;
+        }
+        [self.reason(), self.subreason()]
+            .into_iter()
+            .flatten()
+            .find(|x| x.contains("your country"))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct StreamData {
-    /// These tend to be either audio or video only and are generally higher
-    pub adaptive_formats: Vec<VideoFormat>,
-    /// These generally have both audio and video.
-    pub formats: Option<Vec<VideoFormat>>,
+#[non_exhaustive]
+pub struct ErrorScreen {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_error_message_renderer: Option<PlayerErrorMessage>,
 }
 
-/// Information about the stream and video format.
-///
-/// The [`Self::url`] is not how you stream or download the format. You must call
-/// [`crate::innertube::Innertube::decipher_format()`].
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct VideoFormat {
-    pub itag: u32,
-    pub bitrate: u32,
-    pub quality: Quality,
-    pub mime_type: Mime,
-    pub last_modified: String,
-    pub signature_cipher: Option<String>,
-    pub url: Option<String>,
-    pub average_bitrate: Option<u32>,
-    pub fps: Option<u32>,
-    pub approx_duration_ms: Option<String>,
-    pub content_length: Option<String>,
-    pub height: Option<u32>,
-    pub width: Option<u32>,
-    pub index_range: Option<Range>,
-    pub init_range: Option<Range>,
-    pub projection_type: Option<String>,
-    pub quality_label: Option<String>,
+#[non_exhaustive]
+pub struct PlayerErrorMessage {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reason: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub subreason: Option<String>,
+}
 
-    // audio only
-    pub audio_channels: Option<u8>,
-    pub audio_quality: Option<AudioQuality>,
-    pub audio_sample_rate: Option<String>,
-    pub is_drc: Option<bool>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LiveStreamability {
+    pub live_streamability_renderer: LiveStreamabilityRenderer,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Range {
-    pub end: String,
-    pub start: String,
+#[non_exhaustive]
+pub struct LiveStreamabilityRenderer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offline_slate: Option<OfflineSlate>,
 }
 
-#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-pub enum AudioQuality {
-    #[default]
-    #[serde(rename = "tiny")]
-    Unknown,
-    #[serde(rename = "AUDIO_QUALITY_ULTRALOW")]
-    Ultralow,
-    #[serde(rename = "AUDIO_QUALITY_LOW")]
-    Low,
-    #[serde(rename = "AUDIO_QUALITY_MEDIUM")]
-    Medium,
-    #[serde(rename = "AUDIO_QUALITY_HIGH")]
-    High,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct OfflineSlate {
+    pub live_stream_offline_slate_renderer: OfflineSlateRenderer,
 }
 
-#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-#[serde(rename_all = "lowercase")]
-pub enum Quality {
-    #[default]
-    Tiny,
-    Small,
-    Medium,
-    Large,
-    HD720,
-    HD1080,
-    HD1440,
-    HD2160,
-    HD2880,
-    HighRes,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct OfflineSlateRenderer {
+    /// Unix timestamp in seconds, see [`PlayabilityStatus::scheduled_start_time()`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_start_time: Option<String>,
+}
+
+/// Whether a video is playable, anything but [`PlayabilityState::Ok`] results in
+/// [`Error::Unplayable`] when fetching the video.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PlayabilityState {
+    Ok,
+    LoginRequired,
+    AgeCheckRequired,
+    ContentCheckRequired,
+    Unplayable,
+    Error,
+    LiveStreamOffline,
+    /// Any status not known to this crate, as given by YouTube.
+    Other(String),
+}
+
+impl From<String> for PlayabilityState {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "OK" => PlayabilityState::Ok,
+            "LOGIN_REQUIRED" => PlayabilityState::LoginRequired,
+            "AGE_CHECK_REQUIRED" => PlayabilityState::AgeCheckRequired,
+            "CONTENT_CHECK_REQUIRED" => PlayabilityState::ContentCheckRequired,
+            "UNPLAYABLE" => PlayabilityState::Unplayable,
+            "ERROR" => PlayabilityState::Error,
+            "LIVE_STREAM_OFFLINE" => PlayabilityState::LiveStreamOffline,
+            _ => PlayabilityState::Other(status),
+        }
+    }
+}
+
+impl From<PlayabilityState> for String {
+    fn from(status: PlayabilityState) -> Self {
+        match status {
+            PlayabilityState::Ok => "OK".to_owned(),
+            PlayabilityState::LoginRequired => "LOGIN_REQUIRED".to_owned(),
+            PlayabilityState::AgeCheckRequired => "AGE_CHECK_REQUIRED".to_owned(),
+            PlayabilityState::ContentCheckRequired => "CONTENT_CHECK_REQUIRED".to_owned(),
+            PlayabilityState::Unplayable => "UNPLAYABLE".to_owned(),
+            PlayabilityState::Error => "ERROR".to_owned(),
+            PlayabilityState::LiveStreamOffline => "LIVE_STREAM_OFFLINE".to_owned(),
+            PlayabilityState::Other(status) => status,
+        }
+    }
+}
+
+/// Configuration of the player, only the audio configuration is kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PlayerConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_config: Option<AudioConfig>,
+}
+
+/// Audio configuration of the player, used for loudness normalization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudioConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loudness_db: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perceptual_loudness_db: Option<f64>,
+
+    /// Fields of the audio config which are not modelled by this crate, see [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+/// Live state of a video, returned by [`Video::live_status()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiveStatus {
+    /// A regular video.
+    NotLive,
+    /// A scheduled live stream or premiere which has not started yet.
+    Upcoming,
+    /// A live stream which is currently live, use the HLS or DASH manifest to watch it.
+    Live,
+    /// A live stream which just ended and is still being processed.
+    PostLiveProcessing,
+    /// A past live stream, which can be watched like a regular video.
+    WasLive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Microformat {
+    pub player_microformat_renderer: PlayerMicroformat,
+}
+
+/// Additional metadata of a video, dates are given in ISO 8601 such as
+/// `"2009-10-24T23:57:33-07:00"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PlayerMicroformat {
+    /// Countries the video is available in as ISO 3166 codes, such as `"US"`.
+    #[serde(default)]
+    pub available_countries: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_family_safe: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_number",
+        serialize_with = "serialize_number"
+    )]
+    pub length_seconds: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_broadcast_details: Option<LiveBroadcastDetails>,
+    /// Url of the channel using its handle, such as `"http://www.youtube.com/@RickAstleyYT"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_profile_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_date: Option<String>,
+
+    /// Fields of the microformat which are not modelled by this crate, see [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+impl PlayerMicroformat {
+    /// Returns the handle of the channel from [`Self::owner_profile_url`], if the url uses one.
+    #[must_use]
+    pub fn channel_handle(&self) -> Option<&str> {
+        self.owner_profile_url
+            .as_deref()?
+            .rsplit('/')
+            .next()
+            .filter(|x| x.starts_with('@'))
+    }
+}
+
+/// Details of a live stream, timestamps are given in ISO 8601 such as
+/// `"2024-07-03T17:00:00+00:00"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LiveBroadcastDetails {
+    #[serde(default)]
+    pub is_live_now: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Storyboards {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_storyboard_spec_renderer: Option<StoryboardSpecRenderer>,
+}
+
+/// Storyboard of a video in YouTube's compact format, see [`Video::storyboards()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct StoryboardSpecRenderer {
+    pub spec: String,
+
+    /// Fields of the storyboard which are not modelled by this crate, see [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Captions {
+    pub player_captions_tracklist_renderer: CaptionTracklist,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CaptionTracklist {
+    #[serde(default)]
+    pub caption_tracks: Vec<CaptionTrack>,
+}
+
+/// A caption track of a video, either uploaded or automatically generated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawCaptionTrack", into = "RawCaptionTrack")]
+#[non_exhaustive]
+pub struct CaptionTrack {
+    pub base_url: String,
+    pub name: String,
+    pub language_code: String,
+    pub vss_id: Option<String>,
+    /// `"asr"` for automatically generated tracks.
+    pub kind: Option<String>,
+    pub is_translatable: bool,
+    /// Name as given by Innertube, which is written back as long as [`Self::name`] is unchanged.
+    name_text: serde_json::Value,
+}
+
+/// [`CaptionTrack`] as given by Innertube, with the name as either a plain string or text.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCaptionTrack {
+    base_url: String,
+    name: serde_json::Value,
+    language_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vss_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(default)]
+    is_translatable: bool,
+}
+
+impl TryFrom<RawCaptionTrack> for CaptionTrack {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawCaptionTrack) -> Result<Self, Self::Error> {
+        Ok(CaptionTrack {
+            base_url: raw.base_url,
+            name: AnyText::deserialize(&raw.name)?.into(),
+            language_code: raw.language_code,
+            vss_id: raw.vss_id,
+            kind: raw.kind,
+            is_translatable: raw.is_translatable,
+            name_text: raw.name,
+        })
+    }
+}
+
+impl From<CaptionTrack> for RawCaptionTrack {
+    fn from(track: CaptionTrack) -> Self {
+        let unchanged =
+            AnyText::deserialize(&track.name_text).is_ok_and(|x| String::from(x) == track.name);
+        RawCaptionTrack {
+            base_url: track.base_url,
+            name: if unchanged {
+                track.name_text
+            } else {
+                track.name.into()
+            },
+            language_code: track.language_code,
+            vss_id: track.vss_id,
+            kind: track.kind,
+            is_translatable: track.is_translatable,
+        }
+    }
+}
+
+impl CaptionTrack {
+    /// Returns whether the track is automatically generated by speech recognition.
+    #[must_use]
+    pub fn is_auto_generated(&self) -> bool {
+        self.kind.as_deref() == Some("asr")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct StreamData {
+    /// These tend to be either audio or video only and are generally higher
+    pub adaptive_formats: Vec<VideoFormat>,
+    /// These generally have both audio and video.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formats: Option<Vec<VideoFormat>>,
+    /// Url to the DASH manifest, see [`crate::innertube::Innertube::fetch_dash_manifest()`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dash_manifest_url: Option<String>,
+    /// Url to the HLS playlist, usually only present for livestreams and only for some clients.
+    /// See [`crate::innertube::Innertube::fetch_hls_manifest()`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hls_manifest_url: Option<String>,
+    /// Seconds until the stream urls expire, relative to when the response was received.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in_seconds: Option<String>,
+
+    /// Fields of the streaming data which are not modelled by this crate, see
+    /// [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+impl StreamData {
+    /// Returns when the stream urls expire, taken from the first non-ciphered url in the adaptive
+    /// formats.
+    ///
+    /// Returns `None` if every format is ciphered or no expiry could be found.
+    #[must_use]
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.adaptive_formats
+            .iter()
+            .find_map(|format| format.url.as_deref())
+            .and_then(url_expiry)
+    }
+
+    /// Returns how long the stream urls are valid for after the response was received.
+    #[must_use]
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.expires_in_seconds
+            .as_deref()?
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+    }
+}
+
+/// Information about the stream and video format.
+///
+/// The [`Self::url`] is not how you stream or download the format. You must call
+/// [`crate::innertube::Innertube::decipher_format()`].
+///
+/// The mime string the format was parsed from is kept, and it is written back when serializing
+/// as long as [`Self::mime_type`] is unchanged. So codec profiles such as `avc1.4d401f`, which
+/// [`Mime`] does not keep, survive a round trip.
+///
+/// Formats compare equal only if every field is equal, use [`compare_quality()`] to order them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(remote = "Self", rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct VideoFormat {
+    pub itag: u32,
+    pub bitrate: u32,
+    pub quality: Quality,
+    pub mime_type: Mime,
+    pub last_modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_cipher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_bitrate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approx_duration_ms: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_range: Option<Range>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_range: Option<Range>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projection_type: Option<ProjectionType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_info: Option<ColorInfo>,
+
+    // audio only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_channels: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_quality: Option<AudioQuality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_sample_rate: Option<String>,
+    /// Spatial audio layout of the format, such as `"SPATIAL_AUDIO_TYPE_AMBISONICS_5_1"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial_audio_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_drc: Option<bool>,
+    /// Loudness of the format in decibels relative to YouTube's target loudness, see
+    /// [`Video::loudness_db()`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loudness_db: Option<f64>,
+    /// Loudness of DRC formats after compression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drc_loudness_db: Option<f64>,
+    /// Audio track of the format, only present for videos with multiple audio tracks such as
+    /// dubbed videos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_track: Option<AudioTrack>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xtags: Option<String>,
+
+    /// DRM systems protecting the format, such as `"WIDEVINE"`, only present for protected
+    /// content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drm_families: Option<Vec<String>>,
+
+    /// Fields of the format which are not modelled by this crate, see [`Video::extras`].
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+    /// Mime string as given by Innertube.
+    #[serde(skip)]
+    mime_raw: String,
+}
+
+impl<'de> Deserialize<'de> for VideoFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let mime_raw = value["mimeType"].as_str().unwrap_or_default().to_owned();
+        let mut format = VideoFormat::deserialize(value).map_err(de::Error::custom)?;
+        format.mime_raw = mime_raw;
+        Ok(format)
+    }
+}
+
+impl Serialize for VideoFormat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(mime) = self.mime_str() else {
+            return VideoFormat::serialize(self, serializer);
+        };
+        let mut value = VideoFormat::serialize(self, serde_json::value::Serializer)
+            .map_err(ser::Error::custom)?;
+        value["mimeType"] = mime.into();
+        value.serialize(serializer)
+    }
+}
+
+/// How the video of a format is projected, returned in [`VideoFormat::projection_type`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProjectionType {
+    /// A regular flat video.
+    Rectangular,
+    /// A 360° video.
+    Equirectangular,
+    /// A 360° video with head tracking.
+    EquirectangularThreeDof,
+    /// Any projection not known to this crate, as given by YouTube.
+    Unknown(String),
+}
+
+impl FromStr for ProjectionType {
+    type Err = Infallible;
+
+    fn from_str(projection: &str) -> Result<Self, Self::Err> {
+        Ok(ProjectionType::from(projection.to_owned()))
+    }
+}
+
+impl From<String> for ProjectionType {
+    fn from(projection: String) -> Self {
+        match projection.as_str() {
+            "RECTANGULAR" => ProjectionType::Rectangular,
+            "EQUIRECTANGULAR" => ProjectionType::Equirectangular,
+            "EQUIRECTANGULAR_THREEDOF" => ProjectionType::EquirectangularThreeDof,
+            _ => ProjectionType::Unknown(projection),
+        }
+    }
+}
+
+impl From<ProjectionType> for String {
+    fn from(projection: ProjectionType) -> Self {
+        match projection {
+            ProjectionType::Rectangular => "RECTANGULAR".to_owned(),
+            ProjectionType::Equirectangular => "EQUIRECTANGULAR".to_owned(),
+            ProjectionType::EquirectangularThreeDof => "EQUIRECTANGULAR_THREEDOF".to_owned(),
+            ProjectionType::Unknown(projection) => projection,
+        }
+    }
+}
+
+impl VideoFormat {
+    /// Returns the mime string the format was parsed from, such as
+    /// `video/mp4; codecs="avc1.64001F, mp4a.40.2"`. `None` if [`Self::mime_type`] was changed
+    /// since.
+    #[must_use]
+    pub fn mime_str(&self) -> Option<&str> {
+        (self.mime_raw.parse::<Mime>().ok().as_ref() == Some(&self.mime_type))
+            .then_some(self.mime_raw.as_str())
+    }
+
+    /// Returns the codec strings of [`Self::mime_str()`], video before audio.
+    fn raw_codecs(&self) -> impl Iterator<Item = &str> {
+        self.mime_str()
+            .and_then(|x| between(x, "\"", "\""))
+            .into_iter()
+            .flat_map(|x| x.split(',').map(str::trim))
+    }
+
+    /// Returns the video codec as given in the mime string, such as `"avc1.64001F"`. Unlike
+    /// [`Mime::vcodec()`], this keeps the profile and level. `None` for audio formats.
+    #[must_use]
+    pub fn vcodec_raw(&self) -> Option<&str> {
+        self.mime_type
+            .is_video()
+            .then(|| self.raw_codecs().next())
+            .flatten()
+    }
+
+    /// Returns the audio codec as given in the mime string, such as `"mp4a.40.2"`. `None` for
+    /// video formats without audio.
+    #[must_use]
+    pub fn acodec_raw(&self) -> Option<&str> {
+        let skip = usize::from(self.mime_type.is_video());
+        self.mime_type
+            .has_audio()
+            .then(|| self.raw_codecs().nth(skip))
+            .flatten()
+    }
+
+    /// Returns the profile and level of an AVC video codec, see [`AvcProfile`].
+    #[must_use]
+    pub fn avc_profile(&self) -> Option<AvcProfile> {
+        self.vcodec_raw().and_then(AvcProfile::parse)
+    }
+
+    /// Returns the profile, level and tier of an AV1 video codec, see [`Av1Profile`].
+    #[must_use]
+    pub fn av1_profile(&self) -> Option<Av1Profile> {
+        self.vcodec_raw().and_then(Av1Profile::parse)
+    }
+
+    /// Returns when the url of this format expires.
+    ///
+    /// Only non-ciphered urls are inspected, returns `None` if the format is ciphered or the url
+    /// does not contain an expiry.
+    #[must_use]
+    pub fn url_expires_at(&self) -> Option<SystemTime> {
+        self.url.as_deref().and_then(url_expiry)
+    }
+
+    /// Returns whether the format only contains audio.
+    ///
+    /// This is decided by the mime alone, fields such as `audio_quality` or `audio_channels` are
+    /// not reliable since they may be missing or present on other kinds of formats.
+    #[must_use]
+    pub fn is_audio_only(&self) -> bool {
+        self.mime_type.is_audio()
+    }
+
+    /// Returns whether the format only contains video.
+    #[must_use]
+    pub fn is_video_only(&self) -> bool {
+        matches!(self.mime_type, Mime::Video(_, _, None))
+    }
+
+    /// Returns whether the format contains both audio and video.
+    #[must_use]
+    pub fn is_progressive(&self) -> bool {
+        matches!(self.mime_type, Mime::Video(_, _, Some(_)))
+    }
+
+    /// Returns whether the format is a 360° video, with or without head tracking.
+    #[must_use]
+    pub fn is_360(&self) -> bool {
+        matches!(
+            self.projection_type,
+            Some(ProjectionType::Equirectangular | ProjectionType::EquirectangularThreeDof)
+        )
+    }
+
+    /// Returns the loudness to normalize the format with, the DRC loudness for DRC formats and
+    /// the regular loudness otherwise.
+    #[must_use]
+    pub fn replay_gain_db(&self) -> Option<f64> {
+        if self.is_drc == Some(true) {
+            self.drc_loudness_db
+        } else {
+            self.loudness_db
+        }
+    }
+
+    /// Returns the linear gain to normalize this format with, see
+    /// [`Video::normalization_gain()`] and [`Self::replay_gain_db()`].
+    #[must_use]
+    pub fn normalization_gain(&self) -> Option<f64> {
+        self.replay_gain_db().map(normalization_gain)
+    }
+
+    /// Returns the display name of the audio track, such as `"English (United States)
+    /// original"`, if the video has multiple audio tracks.
+    #[must_use]
+    pub fn track_name(&self) -> Option<&str> {
+        self.audio_track.as_ref().map(|x| x.display_name.as_str())
+    }
+
+    /// Returns the id of the audio track, such as `"en.4"`, if the video has multiple audio
+    /// tracks.
+    ///
+    /// The id is the language followed by a number, the default track is not identified by its
+    /// id but by [`AudioTrack::audio_is_default`], see [`Self::is_default_audio_track()`].
+    #[must_use]
+    pub fn track_id(&self) -> Option<&str> {
+        self.audio_track.as_ref().map(|x| x.id.as_str())
+    }
+
+    /// Returns whether the format belongs to the default audio track, formats without an audio
+    /// track are considered to be default.
+    #[must_use]
+    pub fn is_default_audio_track(&self) -> bool {
+        self.audio_track
+            .as_ref()
+            .is_none_or(|track| track.audio_is_default)
+    }
+
+    /// Returns whether the format has exactly one audio channel.
+    #[must_use]
+    pub fn is_mono(&self) -> bool {
+        self.audio_channels == Some(1)
+    }
+
+    /// Returns whether the format has exactly two audio channels.
+    #[must_use]
+    pub fn is_stereo(&self) -> bool {
+        self.audio_channels == Some(2)
+    }
+
+    /// Returns whether the format has spatial or surround audio, which is either more than two
+    /// audio channels or a [`Self::spatial_audio_type`].
+    #[must_use]
+    pub fn is_spatial(&self) -> bool {
+        self.audio_channels.is_some_and(|x| x > 2)
+            || self
+                .spatial_audio_type
+                .as_deref()
+                .is_some_and(|x| x != "SPATIAL_AUDIO_TYPE_NONE")
+    }
+
+    /// Returns whether the format is protected by DRM, in which case it can not be played
+    /// without a license.
+    #[must_use]
+    pub fn has_drm(&self) -> bool {
+        self.drm_families.as_ref().is_some_and(|x| !x.is_empty())
+    }
+
+    /// Returns whether the format has a url, either plain or ciphered.
+    fn has_stream_url(&self) -> bool {
+        self.url.is_some() || self.signature_cipher.is_some()
+    }
+
+    /// Returns the size of the format in bytes, `None` if missing or malformed.
+    #[must_use]
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length.as_deref()?.parse().ok()
+    }
+
+    /// Returns the approximate duration of the format, `None` if missing or malformed.
+    #[must_use]
+    pub fn approx_duration(&self) -> Option<Duration> {
+        self.approx_duration_ms
+            .as_deref()?
+            .parse()
+            .ok()
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the audio sample rate in hertz, `None` if missing or malformed.
+    #[must_use]
+    pub fn audio_sample_rate(&self) -> Option<u32> {
+        self.audio_sample_rate.as_deref()?.parse().ok()
+    }
+
+    /// Returns the size of the format in bytes, the content length if present or otherwise
+    /// estimated from the bitrate and duration.
+    ///
+    /// Some formats, such as OTF and live formats, lack a content length. The estimate uses the
+    /// average bitrate if available, but it is only an estimate and the actual size may differ.
+    #[must_use]
+    pub fn size_estimate(&self) -> Option<u64> {
+        if let Some(length) = self.content_length() {
+            return Some(length);
+        }
+        let bitrate = self.average_bitrate.unwrap_or(self.bitrate);
+        let duration_ms = u64::try_from(self.approx_duration()?.as_millis()).ok()?;
+        Some(u64::from(bitrate) * duration_ms / 8000)
+    }
+
+    /// Returns the parsed [`Self::quality_label`], see [`QualityLabel::parse()`].
+    #[must_use]
+    pub fn label(&self) -> Option<QualityLabel> {
+        self.quality_label.as_deref().and_then(QualityLabel::parse)
+    }
+
+    /// Returns the width and height of the format. If the format does not include them, the
+    /// height is taken from the quality label and the width is estimated assuming a 16:9 aspect
+    /// ratio. `None` for audio formats.
+    #[must_use]
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            return Some((width, height));
+        }
+        let height = self.label()?.height;
+        Some((height * 16 / 9, height))
+    }
+
+    /// Returns the frame rate of the format, taken from the quality label if the format does not
+    /// include it. `None` for audio formats.
+    #[must_use]
+    pub fn framerate(&self) -> Option<u32> {
+        self.fps.or_else(|| self.label()?.fps)
+    }
+
+    /// Returns whether the format is HDR, see [`ColorInfo::is_hdr()`].
+    #[must_use]
+    pub fn is_hdr(&self) -> bool {
+        self.color_info.as_ref().is_some_and(ColorInfo::is_hdr)
+    }
+
+    /// Returns the byte range of the initialization segment as `(start, end)`, see
+    /// [`Range::to_bytes()`].
+    #[must_use]
+    pub fn init_range_bytes(&self) -> Option<(u64, u64)> {
+        self.init_range.as_ref().and_then(Range::to_bytes)
+    }
+
+    /// Returns the byte range of the segment index as `(start, end)`, see [`Range::to_bytes()`].
+    #[must_use]
+    pub fn index_range_bytes(&self) -> Option<(u64, u64)> {
+        self.index_range.as_ref().and_then(Range::to_bytes)
+    }
+}
+
+/// An audio track of a video with multiple audio tracks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudioTrack {
+    /// Id of the track in the form of language and a number, such as `"en.4"`.
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub audio_is_default: bool,
+}
+
+impl AudioTrack {
+    /// Returns the language of the track, the id without its number, such as `"en"` or
+    /// `"es-419"`.
+    #[must_use]
+    pub fn language(&self) -> &str {
+        self.id.split('.').next().unwrap_or_default()
+    }
+
+    /// Returns whether the track is in the given language, `"en"` also matches regional variants
+    /// such as `"en-US"`.
+    fn is_language(&self, lang: &str) -> bool {
+        let language = self.language();
+        language.eq_ignore_ascii_case(lang)
+            || language
+                .split('-')
+                .next()
+                .is_some_and(|x| x.eq_ignore_ascii_case(lang))
+    }
+}
+
+/// Color information of a video format, such as `COLOR_PRIMARIES_BT709`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ColorInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primaries: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_characteristics: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix_coefficients: Option<String>,
+}
+
+impl ColorInfo {
+    /// Returns whether the color information signals HDR, which is either BT.2020 primaries or a
+    /// PQ (SMPTE ST 2084) or HLG (ARIB STD-B67) transfer.
+    #[must_use]
+    pub fn is_hdr(&self) -> bool {
+        self.primaries
+            .as_deref()
+            .is_some_and(|x| x.ends_with("BT2020"))
+            || self
+                .transfer_characteristics
+                .as_deref()
+                .is_some_and(|x| x.ends_with("SMPTEST2084") || x.ends_with("ARIB_STD_B67"))
+    }
+}
+
+/// Inclusive byte range inside a stream, such as the initialization segment or segment index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Range {
+    pub end: String,
+    pub start: String,
+}
+
+impl Range {
+    /// Parses the range into a `(start, end)` byte offset tuple, returning `None` if either
+    /// offset is not a valid number.
+    #[must_use]
+    pub fn to_bytes(&self) -> Option<(u64, u64)> {
+        Some((self.start.parse().ok()?, self.end.parse().ok()?))
+    }
+}
+
+/// Audio quality of a format, ordered from worst to best.
+///
+/// Qualities not known to this crate deserialize to [`AudioQuality::Other`], which is ordered
+/// below every other quality.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AudioQuality {
+    #[default]
+    #[serde(rename = "tiny")]
+    Unknown,
+    #[serde(rename = "AUDIO_QUALITY_ULTRALOW")]
+    Ultralow,
+    #[serde(rename = "AUDIO_QUALITY_LOW")]
+    Low,
+    #[serde(rename = "AUDIO_QUALITY_MEDIUM")]
+    Medium,
+    #[serde(rename = "AUDIO_QUALITY_HIGH")]
+    High,
+    #[serde(other)]
+    Other,
+}
+
+impl AudioQuality {
+    fn rank(self) -> u8 {
+        match self {
+            AudioQuality::Other => 0,
+            x => x as u8 + 1,
+        }
+    }
+}
+
+impl PartialOrd for AudioQuality {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AudioQuality {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl fmt::Display for AudioQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AudioQuality::Ultralow => "ultralow",
+            AudioQuality::Low => "low",
+            AudioQuality::Medium => "medium",
+            AudioQuality::High => "high",
+            AudioQuality::Unknown | AudioQuality::Other => "unknown",
+        })
+    }
+}
+
+/// Video quality of a format, ordered from worst to best.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    #[default]
+    Tiny,
+    Small,
+    Medium,
+    Large,
+    HD720,
+    HD1080,
+    HD1440,
+    HD2160,
+    HD2880,
+    HighRes,
+    /// A quality not known to this crate, ordered below [`Quality::Tiny`].
+    #[serde(other)]
+    Other,
+}
+
+impl Quality {
+    fn rank(self) -> u8 {
+        match self {
+            Quality::Other => 0,
+            x => x as u8 + 1,
+        }
+    }
+}
+
+impl PartialOrd for Quality {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Quality {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+/// Displays the quality as a resolution label such as `"1080p"`, the lowest and highest
+/// qualities have no fixed resolution and keep their names.
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Quality::Tiny => "tiny",
+            Quality::Small => "small",
+            Quality::Medium => "360p",
+            Quality::Large => "480p",
+            Quality::HD720 => "720p",
+            Quality::HD1080 => "1080p",
+            Quality::HD1440 => "1440p",
+            Quality::HD2160 => "2160p",
+            Quality::HD2880 => "2880p",
+            Quality::HighRes => "highres",
+            Quality::Other => "unknown",
+        })
+    }
+}
+
+/// Resolution and frame rate of a video format, parsed from a quality label such as
+/// `"1080p60 HDR"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityLabel {
+    pub height: u32,
+    /// Frame rate if it is part of the label, YouTube only includes it for high frame rates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    pub hdr: bool,
+}
+
+impl QualityLabel {
+    /// Parses a quality label such as `"144p"`, `"1440p60"` or `"2160p60 HDR"`. An `HDR` or
+    /// `HFR` suffix may also be attached, such as `"1080p60HDR"`. Trailing words other than
+    /// `HDR`, such as `Premium`, are ignored.
+    #[must_use]
+    pub fn parse(label: &str) -> Option<Self> {
+        let mut words = label.split_whitespace();
+        let (height, rest) = words.next()?.split_once('p')?;
+        let fps = rest.trim_end_matches(|x: char| x.is_ascii_alphabetic());
+        let suffix = &rest[fps.len()..];
+        if !(suffix.is_empty()
+            || suffix.eq_ignore_ascii_case("hdr")
+            || suffix.eq_ignore_ascii_case("hfr"))
+        {
+            return None;
+        }
+
+        let fps = if fps.is_empty() {
+            None
+        } else {
+            Some(fps.parse().ok()?)
+        };
+        Some(QualityLabel {
+            height: height.parse().ok()?,
+            fps,
+            hdr: suffix.eq_ignore_ascii_case("hdr") || words.any(|x| x.eq_ignore_ascii_case("hdr")),
+        })
+    }
+}
+
+/// A single segment of the "most replayed" heatmap of a video, returned by
+/// [`crate::innertube::Innertube::heatmap()`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct HeatMarker {
+    /// Start of the segment.
+    pub start: Duration,
+    /// Length of the segment.
+    pub duration: Duration,
+    /// How often the segment is replayed compared to the rest of the video, between 0 and 1.
+    pub intensity: f32,
+}
+
+/// A chapter of a video, returned by [`crate::innertube::Innertube::video_chapters()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChapterInfo {
+    pub title: String,
+    /// Start of the chapter in milliseconds.
+    pub start_ms: u64,
+    /// Thumbnail of the chapter, missing for chapters parsed from the description.
+    pub thumbnail_url: Option<String>,
+}
+
+/// A video recommended alongside another video, returned by
+/// [`crate::innertube::Innertube::related_videos()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RelatedVideo {
+    pub video_id: String,
+    pub title: String,
+    pub channel_name: String,
+}
+
+/// A channel found by [`crate::innertube::Innertube::search_channels()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ChannelSearchResult {
+    pub channel_id: String,
+    pub title: String,
+}
+
+/// A playlist found by [`crate::innertube::Innertube::search_playlists()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PlaylistSearchResult {
+    pub playlist_id: String,
+    pub title: String,
+}
+
+/// Metadata and videos of a playlist, returned by
+/// [`crate::innertube::Innertube::playlist_info()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PlaylistInfo {
+    pub playlist_id: String,
+    pub title: String,
+    pub description: String,
+    /// Name of the channel owning the playlist, missing for generated playlists such as mixes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub thumbnails: Vec<Thumbnail>,
+    /// Total number of videos, which may be more than the videos returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_count: Option<u64>,
+    /// When the playlist was last updated, as displayed by YouTube, such as
+    /// `"Last updated on Jan 1, 2024"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    /// Videos of the first page of the playlist.
+    pub videos: Vec<PlaylistVideo>,
+}
+
+/// A video in a playlist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PlaylistVideo {
+    pub video_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_seconds: Option<u64>,
+    /// Whether the video can be played, deleted or private videos remain in playlists but are not
+    /// playable.
+    pub is_playable: bool,
+}
+
+/// A calendar date, displayed as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+}
+
+impl Date {
+    /// Parses the date of an ISO 8601 date or timestamp, such as `2009-10-24` or
+    /// `2009-10-24T23:57:33-07:00`. The time and offset are ignored.
+    #[must_use]
+    pub fn parse(timestamp: &str) -> Option<Self> {
+        let date = timestamp
+            .split_once('T')
+            .map_or(timestamp, |(date, _)| date);
+        let mut parts = date.splitn(3, '-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+        let date = Date {
+            year: year.parse().ok()?,
+            month: month.parse().ok()?,
+            day: day.parse().ok()?,
+        };
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Deserializes a number given as either a string or a number, such as `"213"`. Anything else,
+/// including numbers which can not be parsed, results in 0.
+fn deserialize_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(x) => x.parse().unwrap_or(0),
+        serde_json::Value::Number(x) => x.as_u64().unwrap_or(0),
+        _ => 0,
+    })
+}
+
+/// Serializes a number as a string, the way Innertube gives counts and lengths.
+fn serialize_number<S: Serializer>(number: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(number)
+}
+
+/// Text which is either a plain string or text as given by Innertube.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnyText {
+    Plain(String),
+    Text(Text),
+}
+
+impl From<AnyText> for String {
+    fn from(text: AnyText) -> Self {
+        match text {
+            AnyText::Plain(text) => text,
+            AnyText::Text(text) => text.text(),
+        }
+    }
+}
+
+/// Deserializes optional text which is either a plain string or text as given by Innertube.
+fn deserialize_optional_text<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<AnyText>::deserialize(deserializer)?.map(Into::into))
+}
+
+/// Token used to fetch the next page of a paginated listing, such as
+/// [`crate::innertube::Innertube::channel_videos()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContinuationToken(pub(crate) String);
+
+impl ContinuationToken {
+    /// Returns the raw token.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ContinuationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A validated YouTube video id, see [`crate::innertube::Innertube::info()`].
+///
+/// Can be created from either a video id or a YouTube url containing one.
+///
+/// ```
+/// # use yinfo::VideoId;
+/// let id = VideoId::try_from("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+/// assert_eq!(id.as_str(), "dQw4w9WgXcQ");
+/// assert!(VideoId::try_from("not a video").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoId(String);
+
+impl VideoId {
+    /// Returns the 11 character video id.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for VideoId {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        get_video_id(value)
+            .map(|id| VideoId(id.to_owned()))
+            .ok_or_else(|| Error::NotYoutubeUrl(value.to_owned()))
+    }
+}
+
+impl TryFrom<&String> for VideoId {
+    type Error = Error;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        VideoId::try_from(value.as_str())
+    }
+}
+
+impl TryFrom<String> for VideoId {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        VideoId::try_from(value.as_str())
+    }
+}
+
+impl From<&VideoId> for VideoId {
+    fn from(value: &VideoId) -> Self {
+        value.clone()
+    }
+}
+
+impl fmt::Display for VideoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for VideoId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Converts loudness relative to the target into a linear gain, without amplifying.
+fn normalization_gain(loudness_db: f64) -> f64 {
+    10f64.powf(-loudness_db.max(0.0) / 20.0)
+}
+
+/// Parse the `expire` query parameter, a unix timestamp, of the given stream url.
+fn url_expiry(url: &str) -> Option<SystemTime> {
+    let url = Url::parse(url).ok()?;
+    let (_, expire) = url.query_pairs().find(|(key, _)| key == "expire")?;
+    let secs = expire.parse::<u64>().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mime::{Acodec, Format, Vcodec},
+        select::FormatSelector,
+    };
+
+    fn video() -> Video {
+        serde_json::from_str(include_str!("../tests/fixtures/player.json")).unwrap()
+    }
+
+    #[test]
+    fn test_format_by_itag() {
+        let video = video();
+        assert_eq!(video.format_by_itag(140).map(|x| x.itag), Some(140));
+        assert_eq!(video.format_by_itag(18).map(|x| x.itag), Some(18));
+        assert!(video.format_by_itag(22).is_none());
+    }
+
+    #[test]
+    fn test_audio_video_formats() {
+        let video = video();
+        let audio: Vec<u32> = video.audio_formats().map(|x| x.itag).collect();
+        let video: Vec<u32> = video.video_formats().map(|x| x.itag).collect();
+        assert_eq!(audio, [140, 249, 251, 251]);
+        assert_eq!(video, [137, 248, 136, 247, 18]);
+    }
+
+    #[test]
+    fn test_url_expiry() {
+        let url = "https://rr1---sn-a5mekn6s.googlevideo.com/videoplayback?expire=1720000000&ei=abc&itag=251";
+        assert_eq!(
+            url_expiry(url),
+            Some(UNIX_EPOCH + Duration::from_secs(1_720_000_000))
+        );
+    }
+
+    #[test]
+    fn test_best_audio_by() {
+        let video = video();
+        assert_eq!(video.best_audio().map(|x| x.itag), Some(251));
+
+        // prefer aac over opus, keeping the rest of the default ordering
+        let aac = video.best_audio_by(|a, b| {
+            let is_aac = |x: &VideoFormat| x.mime_type.acodec() == Some(Acodec::AAC);
+            is_aac(a)
+                .cmp(&is_aac(b))
+                .then_with(|| compare_audio_default(a, b))
+        });
+        assert_eq!(aac.map(|x| x.itag), Some(140));
+    }
+
+    #[test]
+    fn test_format_kinds() {
+        let video = video();
+        let format = video.format_by_itag(140).unwrap();
+        assert!(format.is_audio_only() && !format.is_video_only() && !format.is_progressive());
+        let format = video.format_by_itag(137).unwrap();
+        assert!(!format.is_audio_only() && format.is_video_only() && !format.is_progressive());
+        let format = video.format_by_itag(18).unwrap();
+        assert!(!format.is_audio_only() && !format.is_video_only() && format.is_progressive());
+        assert!(video.all_formats().all(|x| !x.has_drm()));
+    }
+
+    #[test]
+    fn test_projection_type() {
+        for (projection, expected) in [
+            ("RECTANGULAR", ProjectionType::Rectangular),
+            ("EQUIRECTANGULAR", ProjectionType::Equirectangular),
+            (
+                "EQUIRECTANGULAR_THREEDOF",
+                ProjectionType::EquirectangularThreeDof,
+            ),
+            ("MESH", ProjectionType::Unknown("MESH".to_owned())),
+        ] {
+            assert_eq!(projection.parse::<ProjectionType>().unwrap(), expected);
+            let json = serde_json::to_value(&expected).unwrap();
+            assert_eq!(json, projection);
+            assert_eq!(
+                serde_json::from_value::<ProjectionType>(json).unwrap(),
+                expected
+            );
+        }
+
+        let mut video = video();
+        assert_eq!(video.formats_360().count(), 0);
+        let format = video.format_by_itag(137).unwrap();
+        assert_eq!(format.projection_type, Some(ProjectionType::Rectangular));
+        assert!(!format.is_360());
+
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter_mut()
+            .find(|format| format.itag == 137)
+            .unwrap();
+        format.projection_type = Some(ProjectionType::EquirectangularThreeDof);
+        assert!(format.is_360());
+        let itags: Vec<u32> = video.formats_360().map(|format| format.itag).collect();
+        assert_eq!(itags, [137]);
+    }
+
+    #[test]
+    fn test_format_kind_iterators() {
+        let video = video();
+        let itags = |formats: Vec<&VideoFormat>| formats.iter().map(|x| x.itag).collect::<Vec<_>>();
+        assert_eq!(itags(video.progressive_formats().collect()), [18]);
+        assert_eq!(
+            itags(video.video_only_formats().collect()),
+            [137, 248, 136, 247]
+        );
+        assert_eq!(
+            itags(video.audio_only_formats().collect()),
+            [140, 249, 251, 251]
+        );
+    }
+
+    #[test]
+    fn test_numeric_accessors() {
+        let video = video();
+        let format = video.format_by_itag(140).unwrap();
+        assert_eq!(format.content_length(), Some(4_097_212));
+        assert_eq!(
+            format.approx_duration(),
+            Some(Duration::from_millis(253_097))
+        );
+        assert_eq!(format.audio_sample_rate(), Some(44100));
+
+        let json = r#"{
+            "itag": 140,
+            "bitrate": 130000,
+            "quality": "tiny",
+            "mimeType": "audio/mp4; codecs=\"mp4a.40.2\"",
+            "lastModified": "1",
+            "contentLength": "abc",
+            "audioSampleRate": ""
+        }"#;
+        let format: VideoFormat = serde_json::from_str(json).unwrap();
+        assert_eq!(format.content_length(), None);
+        assert_eq!(format.approx_duration(), None);
+        assert_eq!(format.audio_sample_rate(), None);
+    }
+
+    #[test]
+    fn test_captions() {
+        let video = video();
+        let captions = video.captions();
+        assert_eq!(captions.len(), 2);
+        assert_eq!(captions[0].name, "English");
+        assert!(!captions[0].is_auto_generated());
+        assert_eq!(captions[1].name, "English (auto-generated)");
+        assert!(captions[1].is_auto_generated());
+
+        // serialized tracks can be read back
+        let json = serde_json::to_string(&captions[1]).unwrap();
+        let track: CaptionTrack = serde_json::from_str(&json).unwrap();
+        assert_eq!(track.name, captions[1].name);
+    }
+
+    #[test]
+    fn test_format_ord() {
+        let video = video();
+        let mut formats: Vec<_> = video.all_formats().collect();
+        formats.sort_by(|a, b| compare_quality(a, b));
+        let itags: Vec<_> = formats.iter().map(|x| x.itag).collect();
+        assert_eq!(itags, [249, 140, 251, 251, 18, 136, 247, 248, 137]);
+        assert_eq!(formats[3].is_drc, Some(true));
+        assert!(formats
+            .windows(2)
+            .all(|x| compare_quality(x[0], x[1]).is_lt()));
+
+        // equality compares every field, not just the ordering
+        let original = formats[0];
+        let mut refreshed = original.clone();
+        refreshed.url = Some("https://example.com/refreshed".to_owned());
+        assert_eq!(compare_quality(original, &refreshed), Ordering::Equal);
+        assert_ne!(*original, refreshed);
+    }
+
+    #[test]
+    fn test_quality_label() {
+        let label = |height, fps, hdr| Some(QualityLabel { height, fps, hdr });
+        assert_eq!(QualityLabel::parse("144p"), label(144, None, false));
+        assert_eq!(QualityLabel::parse("720p"), label(720, None, false));
+        assert_eq!(QualityLabel::parse("1440p60"), label(1440, Some(60), false));
+        assert_eq!(QualityLabel::parse("2160p60"), label(2160, Some(60), false));
+        assert_eq!(
+            QualityLabel::parse("1080p60 HDR"),
+            label(1080, Some(60), true)
+        );
+        assert_eq!(
+            QualityLabel::parse("1080p Premium"),
+            label(1080, None, false)
+        );
+        assert_eq!(
+            QualityLabel::parse(" 4320p60  HDR "),
+            label(4320, Some(60), true)
+        );
+        assert_eq!(QualityLabel::parse(""), None);
+        assert_eq!(QualityLabel::parse("p60"), None);
+        assert_eq!(QualityLabel::parse("1080"), None);
+        assert_eq!(QualityLabel::parse("1080pHDR"), label(1080, None, true));
+        assert_eq!(
+            QualityLabel::parse("1080p60HFR"),
+            label(1080, Some(60), false)
+        );
+        assert_eq!(QualityLabel::parse("1080p60x"), None);
+
+        let video = video();
+        let format = video.format_by_itag(137).unwrap();
+        assert_eq!(format.label(), label(1080, None, false));
+    }
+
+    #[test]
+    fn test_resolution() {
+        let mut video = video();
+        let format = video.format_by_itag(137).unwrap();
+        assert_eq!(format.resolution(), Some((1920, 1080)));
+        assert_eq!(format.framerate(), Some(25));
+        assert_eq!(video.format_by_itag(140).unwrap().resolution(), None);
+        assert_eq!(video.format_by_itag(140).unwrap().framerate(), None);
+
+        let format = &mut video.streaming_data.adaptive_formats[0];
+        format.width = None;
+        format.height = None;
+        format.fps = None;
+        format.quality_label = Some("720p60".to_owned());
+        assert_eq!(format.resolution(), Some((1280, 720)));
+        assert_eq!(format.framerate(), Some(60));
+    }
+
+    #[test]
+    fn test_unknown_quality() {
+        let json = include_str!("../tests/fixtures/player.json")
+            .replacen(r#""quality": "hd1080""#, r#""quality": "hd4320""#, 1)
+            .replacen("AUDIO_QUALITY_MEDIUM", "AUDIO_QUALITY_ULTRAHIGH", 1);
+        let video: Video = serde_json::from_str(&json).unwrap();
+        let formats: Vec<_> = video.all_formats().collect();
+        assert!(formats.iter().any(|x| x.quality == Quality::Other));
+        assert!(formats
+            .iter()
+            .any(|x| x.audio_quality == Some(AudioQuality::Other)));
+        assert!(Quality::Other < Quality::Tiny);
+        assert!(AudioQuality::Other < AudioQuality::Unknown);
+    }
+
+    #[test]
+    fn test_quality_display() {
+        assert_eq!(Quality::Tiny.to_string(), "tiny");
+        assert_eq!(Quality::Medium.to_string(), "360p");
+        assert_eq!(Quality::HD1080.to_string(), "1080p");
+        assert_eq!(Quality::HighRes.to_string(), "highres");
+        assert_eq!(AudioQuality::Ultralow.to_string(), "ultralow");
+        assert_eq!(AudioQuality::High.to_string(), "high");
+        assert_eq!(AudioQuality::Other.to_string(), "unknown");
+
+        let video = video();
+        let format = video.format_by_itag(137).unwrap();
+        assert_eq!(format.quality.to_string(), "1080p");
+        assert_eq!(format.quality_label.as_deref(), Some("1080p"));
+    }
+
+    #[test]
+    fn test_size_estimate() {
+        let json = r#"{
+            "itag": 140,
+            "bitrate": 130000,
+            "averageBitrate": 128000,
+            "quality": "tiny",
+            "mimeType": "audio/mp4; codecs=\"mp4a.40.2\"",
+            "lastModified": "1",
+            "approxDurationMs": "10000"
+        }"#;
+        let mut format: VideoFormat = serde_json::from_str(json).unwrap();
+        assert_eq!(format.size_estimate(), Some(160_000));
+        format.average_bitrate = None;
+        assert_eq!(format.size_estimate(), Some(162_500));
+        format.content_length = Some("4097212".to_owned());
+        assert_eq!(format.size_estimate(), Some(4_097_212));
+    }
+
+    #[test]
+    fn test_playability_status() {
+        let video = video();
+        assert_eq!(video.playability_status.status, PlayabilityState::Ok);
+
+        let json = r#"{"status": "LOGIN_REQUIRED", "reason": "This video is private"}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(status.status, PlayabilityState::LoginRequired);
+        assert_eq!(status.reason.as_deref(), Some("This video is private"));
+
+        assert!(!status.is_age_restricted());
+
+        let json = r#"{"status": "LOGIN_REQUIRED", "reason": "Sign in to confirm your age"}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert!(status.is_age_restricted());
+
+        let json = r#"{"status": "AGE_CHECK_REQUIRED"}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert!(status.is_age_restricted());
+
+        // recognized without relying on the language of the reason
+        let json = r#"{"status": "LOGIN_REQUIRED", "reason": "Bestätige dein Alter", "desktopLegacyAgeGateReason": 1}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert!(status.is_age_restricted());
+        assert_eq!(
+            serde_json::to_value(&status).unwrap()["desktopLegacyAgeGateReason"],
+            1
+        );
+        let json = r#"{"status": "AGE_VERIFICATION_REQUIRED"}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert!(status.is_age_restricted());
+        assert_eq!(status.geo_restriction(), None);
+
+        let json = r#"{"status": "UNPLAYABLE", "reason": "Video unavailable", "errorScreen": {"playerErrorMessageRenderer": {"subreason": {"simpleText": "The uploader has not made this video available in your country"}}}}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            status.geo_restriction().as_deref(),
+            Some("The uploader has not made this video available in your country")
+        );
+
+        let json = r#"{"status": "SOMETHING_NEW"}"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            status.status,
+            PlayabilityState::Other("SOMETHING_NEW".to_owned())
+        );
+        assert_eq!(
+            serde_json::to_value(&status.status).unwrap(),
+            "SOMETHING_NEW"
+        );
+
+        let json = r#"{
+            "status": "UNPLAYABLE",
+            "errorScreen": {"playerErrorMessageRenderer": {
+                "reason": {"simpleText": "Video unavailable"},
+                "subreason": {"runs": [{"text": "The uploader has not made this video available in your country"}]}
+            }}
+        }"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(status.reason().as_deref(), Some("Video unavailable"));
+        assert_eq!(
+            status.subreason().as_deref(),
+            Some("The uploader has not made this video available in your country")
+        );
+
+        let json = r#"{
+            "status": "LIVE_STREAM_OFFLINE",
+            "reason": "This live event will begin in a few moments.",
+            "liveStreamability": {"liveStreamabilityRenderer": {"offlineSlate": {
+                "liveStreamOfflineSlateRenderer": {"scheduledStartTime": "1720026000"}
+            }}}
+        }"#;
+        let status: PlayabilityStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            status.scheduled_start_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_720_026_000))
+        );
+    }
+
+    #[test]
+    fn test_audio_tracks() {
+        let dubbed: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player_multi_audio.json"))
+                .unwrap();
+        let best = dubbed.best_audio().unwrap();
+        assert_eq!(best.audio_track.as_ref().unwrap().language(), "en");
+        assert!(best.is_default_audio_track());
+
+        let spanish = dubbed.best_audio_for_language("es").unwrap();
+        assert_eq!(spanish.audio_track.as_ref().unwrap().id, "es.3");
+        assert_eq!(spanish.itag, 251);
+        assert!(dubbed.best_audio_for_language("de").is_none());
+
+        let tracks = dubbed.audio_tracks();
+        let names: Vec<&str> = tracks.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "Spanish (Latin America)",
+                "Hindi",
+                "English (United States) original"
+            ]
+        );
+        assert!(tracks.iter().all(|(_, formats)| formats.len() == 2));
+        let hindi = dubbed.best_audio_for_track("hi.3").unwrap();
+        assert_eq!(hindi.track_name(), Some("Hindi"));
+        assert!(dubbed.best_audio_for_track("hi.4").is_none());
+
+        // single audio track videos are unaffected
+        let video = video();
+        assert!(video.all_formats().all(VideoFormat::is_default_audio_track));
+        assert!(video.best_audio_for_language("en").is_none());
+        assert!(video.audio_tracks().is_empty());
+    }
+
+    #[test]
+    fn test_drc_formats() {
+        let video = video();
+        let drc: Vec<&VideoFormat> = video.drc_formats().collect();
+        assert_eq!(drc.len(), 1);
+        assert_eq!(drc[0].itag, 251);
+        assert_eq!(
+            video.non_drc_formats().count() + 1,
+            video.all_formats().count()
+        );
+        assert_ne!(video.best_audio().unwrap().is_drc, Some(true));
+    }
+
+    #[test]
+    fn test_replay_gain_db() {
+        let video = video();
+        let regular = video.format_by_itag(251).unwrap();
+        assert_eq!(regular.replay_gain_db(), Some(-3.470_001_2));
+        let drc = video.drc_formats().next().unwrap();
+        assert_eq!(drc.replay_gain_db(), Some(0.970_001_2));
+        assert_eq!(video.format_by_itag(137).unwrap().replay_gain_db(), None);
+    }
+
+    #[test]
+    fn test_formats_expire_at() {
+        let mut video = video();
+        assert_eq!(
+            video.streaming_data.expires_in(),
+            Some(Duration::from_secs(21540))
+        );
+        assert_eq!(
+            video.formats_expire_at(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_720_021_540))
+        );
+
+        // without urls the relative expiry is used
+        for format in &mut video.streaming_data.adaptive_formats {
+            format.url = None;
+        }
+        assert_eq!(video.formats_expire_at(), None);
+        video.fetched_at = Some(UNIX_EPOCH);
+        assert_eq!(
+            video.formats_expire_at(),
+            Some(UNIX_EPOCH + Duration::from_secs(21540))
+        );
+    }
+
+    #[test]
+    fn test_normalization_gain() {
+        let video = video();
+        // quieter than the target, so not amplified
+        assert_eq!(video.normalization_gain(), Some(1.0));
+        let drc = video.drc_formats().next().unwrap();
+        let gain = drc.normalization_gain().unwrap();
+        assert!((gain - 0.894_4).abs() < 1e-3);
+        assert!((normalization_gain(6.0) - 0.501_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_video_details() {
+        let video = video();
+        let details = &video.video_details;
+        assert!(details.short_description.ends_with("\n\n"));
+        assert_eq!(
+            details.description_text(),
+            "The official video for “Never Gonna Give You Up” by Rick Astley."
+        );
+        assert_eq!(details.age_limit, None);
+        assert_eq!(details.is_family_safe, None);
+        assert_eq!(details.length_seconds, 253);
+        assert_eq!(details.view_count, 1_533_028_541);
+        assert_eq!(video.duration(), Some(Duration::from_secs(253)));
+    }
+
+    #[test]
+    fn test_extras() {
+        let video = video();
+        assert!(video.extras.is_empty());
+        let format = video.format_by_itag(140).unwrap();
+        assert_eq!(format.extras.len(), 1);
+        assert_eq!(format.extras["highReplication"], true);
+
+        let json = include_str!("../tests/fixtures/player.json").replacen(
+            r#""playerConfig": {"#,
+            r#""newSignal": {"enabled": true}, "playerConfig": {"#,
+            1,
+        );
+        let video: Video = serde_json::from_str(&json).unwrap();
+        assert_eq!(video.extras["newSignal"]["enabled"], true);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let video = video();
+        let json = serde_json::to_string(&video).unwrap();
+        let parsed: Video = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, video);
+
+        // the time of fetching is not part of the response
+        let mut fetched = parsed.clone();
+        fetched.fetched_at = Some(SystemTime::now());
+        assert_eq!(fetched, video);
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&video).unwrap()
+        );
+
+        let video: Video =
+            serde_json::from_str(include_str!("../tests/fixtures/player_multi_audio.json"))
+                .unwrap();
+        let parsed: Video = serde_json::from_value(serde_json::to_value(&video).unwrap()).unwrap();
+        assert_eq!(parsed, video);
+    }
+
+    #[test]
+    fn test_raw_codecs() {
+        let mut video = video();
+        let high = video.format_by_itag(137).unwrap();
+        assert_eq!(high.mime_str(), Some(r#"video/mp4; codecs="avc1.640028""#));
+        assert_eq!(high.vcodec_raw(), Some("avc1.640028"));
+        assert_eq!(high.acodec_raw(), None);
+        let profile = high.avc_profile().unwrap();
+        assert_eq!((profile.profile, profile.level), (100, 40));
+        assert_eq!(high.av1_profile(), None);
+
+        let muxed = video.format_by_itag(18).unwrap();
+        assert_eq!(muxed.vcodec_raw(), Some("avc1.42001E"));
+        assert_eq!(muxed.acodec_raw(), Some("mp4a.40.2"));
+        assert_eq!(
+            muxed.avc_profile().unwrap().profile_name(),
+            Some("Baseline")
+        );
+        let audio = video.format_by_itag(140).unwrap();
+        assert_eq!(audio.vcodec_raw(), None);
+        assert_eq!(audio.acodec_raw(), Some("mp4a.40.2"));
+
+        let mut json = serde_json::to_value(high).unwrap();
+        json["mimeType"] = r#"video/mp4; codecs="av01.0.08M.08""#.into();
+        let av1: VideoFormat = serde_json::from_value(json).unwrap();
+        assert_eq!(av1.av1_profile().map(|x| x.level), Some(8));
+        assert_eq!(av1.avc_profile(), None);
+
+        // the raw strings no longer apply once the mime is changed
+        let format = &mut video.streaming_data.adaptive_formats[0];
+        format.mime_type = Mime::Video(Format::Webm, Vcodec::VP9, None);
+        assert_eq!(format.mime_str(), None);
+        assert_eq!(format.vcodec_raw(), None);
+    }
+
+    #[test]
+    fn test_fields_kept() {
+        // the serialized video must equal the response it was parsed from, except for ordering
+        for json in [
+            include_str!("../tests/fixtures/player.json"),
+            include_str!("../tests/fixtures/player_multi_audio.json"),
+        ] {
+            let original: serde_json::Value = serde_json::from_str(json).unwrap();
+            let video: Video = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_value(&video).unwrap(), original);
+        }
+
+        let mut video = video();
+        let track = &mut video
+            .captions
+            .as_mut()
+            .unwrap()
+            .player_captions_tracklist_renderer
+            .caption_tracks[0];
+        track.name = "Renamed".to_owned();
+        let json = serde_json::to_value(&video).unwrap();
+        assert_eq!(
+            json["captions"]["playerCaptionsTracklistRenderer"]["captionTracks"][0]["name"],
+            "Renamed"
+        );
+        let copy: Video = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            copy.captions().first().map(|x| x.name.as_str()),
+            Some("Renamed")
+        );
+
+        // the original mime string is only written back while the mime is unchanged
+        let format = &mut video.streaming_data.adaptive_formats[0];
+        let json = serde_json::to_value(&*format).unwrap();
+        assert_eq!(json["mimeType"], r#"video/mp4; codecs="avc1.640028""#);
+        format.mime_type = Mime::Video(Format::Webm, Vcodec::VP9, None);
+        let json = serde_json::to_value(&*format).unwrap();
+        assert_eq!(json["mimeType"], r#"video/webm; codecs="vp9""#);
+    }
+
+    #[test]
+    fn test_clone() {
+        let video = video();
+        let copy = video.clone();
+        assert_eq!(video, copy);
+        assert_eq!(video.video_details, copy.video_details);
+        assert_eq!(
+            video.streaming_data.adaptive_formats[0].mime_type,
+            copy.streaming_data.adaptive_formats[0].mime_type
+        );
+    }
+
+    #[test]
+    fn test_storyboards() {
+        let mut video = video();
+        let storyboards = video.storyboards();
+        assert_eq!(storyboards.len(), 3);
+        assert_eq!(storyboards[1].thumbnail_width, 80);
+        assert_eq!(storyboards[1].sheet_count(), 2);
+
+        video.storyboards = None;
+        assert!(video.storyboards().is_empty());
+    }
+
+    #[test]
+    fn test_microformat() {
+        let mut video = video();
+        let date = Date {
+            year: 2009,
+            month: 10,
+            day: 24,
+        };
+        assert_eq!(video.publish_date(), Some(date));
+        assert_eq!(video.upload_date(), Some(date));
+        assert_eq!(date.to_string(), "2009-10-24");
+        assert_eq!(Date::parse("2009-10-24"), Some(date));
+        assert_eq!(Date::parse("2009-13-24"), None);
+        assert_eq!(Date::parse("24.10.2009"), None);
+        assert_eq!(video.category(), Some("Music"));
+        assert_eq!(video.available_countries(), ["CA", "DE", "GB", "US"]);
+        assert_eq!(video.available_in("us"), Some(true));
+        assert_eq!(video.available_in("FR"), Some(false));
+
+        let microformat = video.microformat().unwrap();
+        assert_eq!(microformat.length_seconds, 253);
+        assert_eq!(microformat.is_family_safe, Some(true));
+        assert_eq!(microformat.channel_handle(), Some("@RickAstleyYT"));
+        assert_eq!(
+            video.channel_url(),
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw"
+        );
+
+        video.microformat = None;
+        assert_eq!(video.publish_date(), None);
+        assert!(video.available_countries().is_empty());
+        assert_eq!(video.available_in("US"), None);
+    }
+
+    #[test]
+    fn test_live_status() {
+        let mut video = video();
+        assert_eq!(video.live_status(), LiveStatus::NotLive);
+        assert!(video.live_broadcast_details().is_none());
+
+        video.microformat = serde_json::from_str(
+            r#"{"playerMicroformatRenderer": {"liveBroadcastDetails": {
+                "isLiveNow": true,
+                "startTimestamp": "2024-07-03T17:00:00+00:00"
+            }}}"#,
+        )
+        .unwrap();
+        video.video_details.is_live_content = true;
+        assert_eq!(video.live_status(), LiveStatus::Live);
+
+        let details = video
+            .microformat
+            .as_mut()
+            .unwrap()
+            .player_microformat_renderer
+            .live_broadcast_details
+            .as_mut()
+            .unwrap();
+        details.is_live_now = false;
+        details.end_timestamp = Some("2024-07-03T19:00:00+00:00".to_owned());
+        assert_eq!(video.live_status(), LiveStatus::WasLive);
+
+        video.video_details.is_post_live_dvr = Some(true);
+        assert_eq!(video.live_status(), LiveStatus::PostLiveProcessing);
+
+        video.video_details.is_upcoming = Some(true);
+        assert_eq!(video.live_status(), LiveStatus::Upcoming);
+    }
+
+    #[test]
+    fn test_video_accessors() {
+        let mut video = video();
+        assert_eq!(video.id(), "dQw4w9WgXcQ");
+        assert_eq!(
+            video.title(),
+            "Rick Astley - Never Gonna Give You Up (Official Music Video)"
+        );
+        assert_eq!(video.author(), "Rick Astley");
+        assert_eq!(
+            video.thumbnail_url(),
+            Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg")
+        );
+
+        video.video_details.length_seconds = 0;
+        assert_eq!(video.duration(), Some(Duration::from_millis(253_097)));
+        video.streaming_data.adaptive_formats.clear();
+        video.streaming_data.formats = None;
+        assert_eq!(video.duration(), None);
+    }
+
+    #[test]
+    fn test_video_details_numbers() {
+        let details = serde_json::to_value(&video().video_details).unwrap();
+        let parse = |length: serde_json::Value, views: serde_json::Value| {
+            let mut details = details.clone();
+            details["lengthSeconds"] = length;
+            details["viewCount"] = views;
+            serde_json::from_value::<VideoDetails>(details).unwrap()
+        };
+
+        let details = parse("abc".into(), serde_json::Value::Null);
+        assert_eq!((details.length_seconds, details.view_count), (0, 0));
+        // serialized details are read back
+        let details = parse(30.into(), 1_000.into());
+        assert_eq!((details.length_seconds, details.view_count), (30, 1_000));
+    }
+
+    #[test]
+    fn test_worst_formats() {
+        let video = video();
+        assert_eq!(video.worst_audio().map(|x| x.itag), Some(249));
+        assert_eq!(video.worst_video().map(|x| x.itag), Some(18));
+        assert_eq!(video.smallest_audio().map(|x| x.itag), Some(249));
+    }
+
+    #[test]
+    fn test_loudness_db() {
+        let video = video();
+        assert_eq!(video.loudness_db(), Some(-3.470_001_2));
+    }
+
+    #[test]
+    fn test_unknown_codec() {
+        let json = include_str!("../tests/fixtures/player.json").replacen(
+            r#"video/webm; codecs=\"vp9\""#,
+            r#"video/webm; codecs=\"vp10.00.51.08\""#,
+            1,
+        );
+        let original = video();
+        let video: Video = serde_json::from_str(&json).unwrap();
+        let format = video.format_by_itag(248).unwrap();
+        assert_eq!(format.mime_type.vcodec(), Some(Vcodec::Unknown));
+        assert_eq!(format.vcodec_raw(), Some("vp10.00.51.08"));
+        assert_eq!(video.all_formats().count(), original.all_formats().count());
+
+        // the unknown format is ranked last despite its quality
+        assert_eq!(original.best_video().map(|x| x.itag), Some(248));
+        assert_eq!(video.best_video().map(|x| x.itag), Some(137));
+        assert_eq!(video.best_audio().map(|x| x.itag), Some(251));
+        let selector = FormatSelector::new().container(Format::Webm);
+        assert_eq!(selector.best(&original).map(|x| x.itag), Some(248));
+        assert_eq!(selector.best(&video).map(|x| x.itag), Some(247));
+    }
+
+    #[test]
+    fn test_formats_for_quality() {
+        let video = video();
+        let itags = |formats: Vec<&VideoFormat>| formats.iter().map(|x| x.itag).collect::<Vec<_>>();
+        assert_eq!(
+            itags(video.formats_for_quality(Quality::HD1080).collect()),
+            [137, 248]
+        );
+        assert_eq!(
+            itags(video.formats_at_least_quality(Quality::HD720).collect()),
+            [137, 248, 136, 247]
+        );
+        assert_eq!(video.formats_at_least_quality(Quality::Tiny).count(), 5);
+        assert_eq!(video.formats_for_quality(Quality::HD2160).count(), 0);
+
+        assert_eq!(
+            itags(video.formats_for_audio_quality(AudioQuality::Low).collect()),
+            [249, 18]
+        );
+        assert_eq!(
+            itags(
+                video
+                    .formats_at_least_audio_quality(AudioQuality::Medium)
+                    .collect()
+            ),
+            [140, 251, 251]
+        );
+    }
+
+    #[test]
+    fn test_spatial_audio() {
+        let mut video = video();
+        assert!(!video.has_spatial_audio());
+        let format = video.format_by_itag(251).unwrap();
+        assert!(format.is_stereo());
+        assert!(!format.is_mono());
+        assert!(!format.is_spatial());
+
+        let format = video
+            .streaming_data
+            .adaptive_formats
+            .iter_mut()
+            .find(|x| x.itag == 251)
+            .unwrap();
+        format.audio_channels = Some(6);
+        assert!(format.is_spatial());
+        assert!(!format.is_stereo());
+
+        format.audio_channels = Some(4);
+        format.spatial_audio_type = Some("SPATIAL_AUDIO_TYPE_NONE".to_owned());
+        assert!(format.is_spatial());
+        format.audio_channels = Some(1);
+        assert!(format.is_mono());
+        assert!(!format.is_spatial());
+        format.spatial_audio_type = Some("SPATIAL_AUDIO_TYPE_AMBISONICS_QUAD".to_owned());
+        assert!(format.is_spatial());
+        assert!(video.has_spatial_audio());
+    }
+
+    #[test]
+    fn test_is_hdr() {
+        let video = video();
+        assert!(video.all_formats().all(|x| !x.is_hdr()));
+
+        let color_info = ColorInfo {
+            primaries: Some("COLOR_PRIMARIES_BT2020".to_owned()),
+            transfer_characteristics: Some("COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084".to_owned()),
+            matrix_coefficients: Some("COLOR_MATRIX_COEFFICIENTS_BT2020_NCL".to_owned()),
+        };
+        assert!(color_info.is_hdr());
+    }
+
+    #[test]
+    fn test_range_to_bytes() {
+        let range = Range {
+            start: "0".to_owned(),
+            end: "740".to_owned(),
+        };
+        assert_eq!(range.to_bytes(), Some((0, 740)));
+
+        let range = Range {
+            start: "0".to_owned(),
+            end: String::new(),
+        };
+        assert_eq!(range.to_bytes(), None);
+    }
+
+    #[test]
+    fn test_url_expiry_missing() {
+        let strings = [
+            "https://rr1---sn-a5mekn6s.googlevideo.com/videoplayback?itag=251",
+            "https://rr1---sn-a5mekn6s.googlevideo.com/videoplayback?expire=soon",
+            "not a url",
+        ];
+        for str in strings {
+            assert_eq!(url_expiry(str), None);
+        }
+    }
 }
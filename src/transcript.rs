@@ -0,0 +1,270 @@
+use once_cell::sync::Lazy;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+
+/// Format a transcript is requested in from the timedtext endpoint, see
+/// [`crate::innertube::Innertube::transcript()`].
+///
+/// All formats are parsed into the same [`TranscriptEntry`], they only differ in how much
+/// styling YouTube includes, which is discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// YouTube's own xml format, this is the most reliable.
+    Srv3,
+    Ttml,
+    Vtt,
+}
+
+impl TranscriptFormat {
+    /// Returns the value of the `fmt` parameter for the timedtext endpoint.
+    pub(crate) fn param(self) -> &'static str {
+        match self {
+            TranscriptFormat::Srv3 => "srv3",
+            TranscriptFormat::Ttml => "ttml",
+            TranscriptFormat::Vtt => "vtt",
+        }
+    }
+
+    /// Parses a transcript given in this format.
+    pub(crate) fn parse(self, body: &str) -> Result<Vec<TranscriptEntry>, Error> {
+        match self {
+            TranscriptFormat::Srv3 => parse_xml(body, "t", "d", parse_millis),
+            TranscriptFormat::Ttml => parse_xml(body, "begin", "end", parse_clock),
+            TranscriptFormat::Vtt => parse_vtt(body),
+        }
+    }
+}
+
+/// A single cue of a transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Parses the `<p>` elements of srv3 and ttml documents. In srv3 the second attribute is the
+/// duration of the cue, whereas in ttml it is the end.
+fn parse_xml(
+    xml: &str,
+    start_attr: &str,
+    end_attr: &str,
+    parse_time: fn(&str) -> Option<u64>,
+) -> Result<Vec<TranscriptEntry>, Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut current: Option<TranscriptEntry> = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) if e.local_name().as_ref() == b"p" => {
+                let start = time_attribute(&e, start_attr, parse_time)?;
+                let end = time_attribute(&e, end_attr, parse_time)?;
+                let end = if start_attr == "t" { start + end } else { end };
+                current = Some(TranscriptEntry {
+                    start_ms: start,
+                    end_ms: end,
+                    text: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(entry) = &mut current {
+                    entry.text.push_str(&text.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"br" => {
+                if let Some(entry) = &mut current {
+                    entry.text.push('\n');
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"p" => {
+                if let Some(mut entry) = current.take() {
+                    entry.text = entry.text.trim().to_owned();
+                    if !entry.text.is_empty() {
+                        entries.push(entry);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+fn time_attribute(
+    e: &BytesStart,
+    name: &str,
+    parse_time: fn(&str) -> Option<u64>,
+) -> Result<u64, Error> {
+    let value = e
+        .try_get_attribute(name)
+        .map_err(xml_error)?
+        .ok_or_else(|| Error::Transcript(format!("missing attribute {name}")))?
+        .unescape_value()
+        .map_err(xml_error)?;
+    parse_time(&value).ok_or_else(|| Error::Transcript(format!("invalid time {value}")))
+}
+
+fn xml_error(e: impl std::fmt::Display) -> Error {
+    Error::Transcript(e.to_string())
+}
+
+/// Parses the cues of a vtt document, inline tags such as the word timings of automatically
+/// generated captions are removed.
+fn parse_vtt(vtt: &str) -> Result<Vec<TranscriptEntry>, Error> {
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+    let mut entries = Vec::new();
+    let mut lines = vtt.lines();
+    while let Some(line) = lines.next() {
+        let Some((start, rest)) = line.split_once("-->") else {
+            continue;
+        };
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let (Some(start_ms), Some(end_ms)) = (parse_clock(start.trim()), parse_clock(end)) else {
+            return Err(Error::Transcript(format!("invalid cue timing {line}")));
+        };
+
+        let text: Vec<&str> = lines
+            .by_ref()
+            .take_while(|x| !x.trim().is_empty())
+            .collect();
+        let text = TAG
+            .replace_all(&text.join("\n"), "")
+            .replace("&nbsp;", " ")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&");
+        let text = text.trim();
+        if !text.is_empty() {
+            entries.push(TranscriptEntry {
+                start_ms,
+                end_ms,
+                text: text.to_owned(),
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_millis(time: &str) -> Option<u64> {
+    time.parse().ok()
+}
+
+/// Parses clock times such as `01:02:03.456` or `02:03.456`, as well as offsets such as `1.5s`.
+fn parse_clock(time: &str) -> Option<u64> {
+    if let Some(seconds) = time.strip_suffix('s') {
+        return parse_seconds(seconds);
+    }
+
+    let mut parts = time.rsplit(':');
+    let mut ms = parse_seconds(parts.next()?)?;
+    for unit in [60_000, 3_600_000] {
+        if let Some(part) = parts.next() {
+            ms += part.parse::<u64>().ok()? * unit;
+        }
+    }
+    parts.next().is_none().then_some(ms)
+}
+
+/// Parses seconds with an optional fraction, such as `3.456`, into milliseconds.
+fn parse_seconds(seconds: &str) -> Option<u64> {
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let fraction = format!("{fraction:0<3}");
+    Some(whole.parse::<u64>().ok()? * 1000 + fraction.get(..3)?.parse::<u64>().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(start_ms: u64, end_ms: u64, text: &str) -> TranscriptEntry {
+        TranscriptEntry {
+            start_ms,
+            end_ms,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse_srv3() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+<body>
+<p t="1360" d="1680" w="1"><s ac="0">We&#39;re</s><s t="240" ac="0"> no</s><s t="480" ac="0"> strangers</s></p>
+<p t="3040" d="10" w="1" a="1">
+</p>
+<p t="3050" d="2000">to love &amp; rules</p>
+</body>
+</timedtext>"#;
+        assert_eq!(
+            TranscriptFormat::Srv3.parse(xml).unwrap(),
+            [
+                entry(1360, 3040, "We're no strangers"),
+                entry(3050, 5050, "to love & rules")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ttml() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?>
+<tt xml:lang="en" xmlns="http://www.w3.org/ns/ttml"><body><div>
+<p begin="00:00:01.360" end="00:00:03.040" style="s2">We&#39;re no strangers<br />to love</p>
+<p begin="00:01:03.5" end="00:01:05.000">You know the rules</p>
+</div></body></tt>"#;
+        assert_eq!(
+            TranscriptFormat::Ttml.parse(xml).unwrap(),
+            [
+                entry(1360, 3040, "We're no strangers\nto love"),
+                entry(63_500, 65_000, "You know the rules")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let vtt = "WEBVTT
+Kind: captions
+Language: en
+
+00:00:01.360 --> 00:00:03.040 align:start position:0%
+We're<00:00:01.600><c> no</c><00:00:01.840><c> strangers</c>
+
+00:01:03.500 --> 00:01:05.000
+to love &amp; rules
+";
+        assert_eq!(
+            TranscriptFormat::Vtt.parse(vtt).unwrap(),
+            [
+                entry(1360, 3040, "We're no strangers"),
+                entry(63_500, 65_000, "to love & rules")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_time() {
+        let xml = r#"<timedtext><body><p t="abc" d="10">text</p></body></timedtext>"#;
+        assert!(TranscriptFormat::Srv3.parse(xml).is_err());
+        assert!(TranscriptFormat::Vtt
+            .parse("WEBVTT\n\nabc --> def\ntext\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("01:02:03.456"), Some(3_723_456));
+        assert_eq!(parse_clock("02:03.4"), Some(123_400));
+        assert_eq!(parse_clock("1.5s"), Some(1500));
+        assert_eq!(parse_clock("1:2:3:4.000"), None);
+    }
+}
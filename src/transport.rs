@@ -0,0 +1,227 @@
+use std::{fmt, future::Future, pin::Pin, time::Duration};
+
+use http::HeaderMap;
+use tokio::{
+    sync::Mutex,
+    time::{sleep_until, Instant},
+};
+
+use crate::errors::Error;
+
+/// Boxed future returned by [`HttpTransport`], as async functions in traits can not be used
+/// through `dyn`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Response of a request sent through a [`HttpTransport`].
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Returns whether the status is a client or server error.
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        (400..600).contains(&self.status)
+    }
+
+    /// Turns error statuses into [`Error::HttpStatus`].
+    pub(crate) fn error_for_status(self) -> Result<Self, Error> {
+        if self.is_error() {
+            return Err(Error::HttpStatus(self.status, self.body));
+        }
+        Ok(self)
+    }
+}
+
+/// Transport used by [`crate::innertube::Innertube`] to send http requests.
+///
+/// An implementation for `reqwest::Client` is provided with the `reqwest` feature, which is
+/// enabled by default. Implement this to use a different http client, or to serve canned
+/// responses in tests. Errors of the underlying client can be returned as
+/// [`Error::Transport`].
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    /// Sends a POST request with the given json body.
+    fn post_json<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+        body: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>>;
+
+    /// Sends a GET request, returning the body as text.
+    fn get_text<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>>;
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for reqwest::Client {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+        body: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move {
+            let res = self.post(url).headers(headers).json(body).send().await?;
+            Ok(HttpResponse {
+                status: res.status().as_u16(),
+                body: res.text().await?,
+            })
+        })
+    }
+
+    fn get_text<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move {
+            let res = self.get(url).headers(headers).send().await?;
+            Ok(HttpResponse {
+                status: res.status().as_u16(),
+                body: res.text().await?,
+            })
+        })
+    }
+}
+
+/// Transport limiting how many requests are sent per second, see
+/// [`crate::innertube::Config::requests_per_second`].
+///
+/// This is a token bucket holding a single token, so requests are spaced out evenly instead of
+/// being sent in bursts. Requests wait for their turn in the order they were made.
+#[derive(Debug)]
+pub(crate) struct RateLimited {
+    inner: Box<dyn HttpTransport>,
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+/// Longest interval between requests, slower rates would overflow the interval.
+const MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+impl RateLimited {
+    /// Wraps the transport, returning it unchanged if the rate is not positive and finite. Rates
+    /// slower than one request per [`MAX_INTERVAL`] are limited to that instead.
+    pub(crate) fn wrap(inner: Box<dyn HttpTransport>, per_second: f64) -> Box<dyn HttpTransport> {
+        if !(per_second.is_finite() && per_second > 0.0) {
+            return inner;
+        }
+        let interval = Duration::try_from_secs_f64(1.0 / per_second)
+            .map_or(MAX_INTERVAL, |x| x.min(MAX_INTERVAL));
+        if interval.is_zero() {
+            return inner;
+        }
+        Box::new(RateLimited {
+            inner,
+            interval,
+            next: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Waits until the next request may be sent.
+    async fn acquire(&self) {
+        let at = {
+            let mut next = self.next.lock().await;
+            let at = (*next).max(Instant::now());
+            *next = at + self.interval;
+            at
+        };
+        sleep_until(at).await;
+    }
+}
+
+impl HttpTransport for RateLimited {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+        body: &'a serde_json::Value,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move {
+            self.acquire().await;
+            self.inner.post_json(url, headers, body).await
+        })
+    }
+
+    fn get_text<'a>(
+        &'a self,
+        url: &'a str,
+        headers: HeaderMap,
+    ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+        Box::pin(async move {
+            self.acquire().await;
+            self.inner.get_text(url, headers).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Ok200;
+
+    impl HttpTransport for Ok200 {
+        fn post_json<'a>(
+            &'a self,
+            _url: &'a str,
+            _headers: HeaderMap,
+            _body: &'a serde_json::Value,
+        ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+            Box::pin(async move {
+                Ok(HttpResponse {
+                    status: 200,
+                    body: String::new(),
+                })
+            })
+        }
+
+        fn get_text<'a>(
+            &'a self,
+            _url: &'a str,
+            _headers: HeaderMap,
+        ) -> BoxFuture<'a, Result<HttpResponse, Error>> {
+            Box::pin(async move {
+                Ok(HttpResponse {
+                    status: 200,
+                    body: String::new(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let http = RateLimited::wrap(Box::new(Ok200), 20.0);
+        let start = Instant::now();
+        for _ in 0..3 {
+            http.get_text("", HeaderMap::new()).await.unwrap();
+            http.post_json("", HeaderMap::new(), &serde_json::Value::Null)
+                .await
+                .unwrap();
+        }
+        // the first request is sent right away, the others 50ms apart
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_rate_limited_invalid() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let http = RateLimited::wrap(Box::new(Ok200), rate);
+            assert!(format!("{http:?}").starts_with("Ok200"));
+        }
+
+        // tiny rates are clamped rather than overflowing into no limit
+        for rate in [1e-6, 1e-300, f64::MIN_POSITIVE] {
+            let http = RateLimited::wrap(Box::new(Ok200), rate);
+            assert!(format!("{http:?}").contains("interval: 86400s"), "{rate}");
+        }
+    }
+}
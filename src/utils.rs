@@ -1,12 +1,21 @@
-/// Return the substring between the two patterns in the hay.
-pub fn between<'a>(hay: &'a str, start_pattern: &'a str, end_pattern: &'a str) -> &'a str {
-    let start = hay.find(start_pattern);
-    if start.is_some() {
-        let start_pos = start.unwrap() + start_pattern.len();
-        let substr = &hay[start_pos..];
-        let end_pos = substr.find(end_pattern).unwrap_or_default();
-        return &substr[..end_pos];
+/// Return the substring between the first occurrence of the start pattern and the following
+/// occurrence of the end pattern, `None` if either pattern is not found.
+pub fn between<'a>(hay: &'a str, start_pattern: &str, end_pattern: &str) -> Option<&'a str> {
+    let start = hay.find(start_pattern)? + start_pattern.len();
+    let substr = &hay[start..];
+    let end = substr.find(end_pattern)?;
+    Some(&substr[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between() {
+        assert_eq!(between("a(b,c)", "(", ","), Some("b"));
+        assert_eq!(between("a(,c)", "(", ","), Some(""));
+        assert_eq!(between("a(b)", ",", ")"), None);
+        assert_eq!(between("a(b", "(", ")"), None);
     }
-    // TODO: option instead?
-    ""
 }
@@ -0,0 +1,12 @@
+var _yt_player={};(function(g){var window=this;
+/* Written in the shape of a trimmed player js, not a capture. Only the parts used for deciphering are kept. */
+var Xy={Bo:function(a,b){a.splice(0,b)},
+Fo:function(a){a.reverse()},
+Ho:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Wpa=function(a){a=a.split("");Xy.Ho(a,3);Xy.Fo(a,1);Xy.Bo(a,2);return a.join("")};
+g.xo=function(a,b,c,d){c&&d.set(b,encodeURIComponent(Wpa(c)))};
+Qna=function(a){var b=a.split(""),c=[b.length];try{b.reverse()}catch(d){return"enhanced_except_gZ8B_w8_"+a}return b.join("")};
+var Tna=[Qna];
+g.Zo=function(a){var b;(b=a.get("n"))&&(b=Tna[0](b),a.set("n",b))};
+g.Yo={sts:19876,signatureTimestamp:19876};
+})(_yt_player);